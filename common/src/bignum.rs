@@ -0,0 +1,220 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign};
+
+/// Arbitrary-precision unsigned integer supporting the few operations the
+/// puzzles need: addition, comparison and decimal formatting.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    // Little-endian base-2^64 limbs without trailing zero limbs.
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        match self.limbs.as_slice() {
+            [] => Some(0),
+            [lo] => Some(*lo as u128),
+            [lo, hi] => Some(((*hi as u128) << 64) | *lo as u128),
+            _ => None,
+        }
+    }
+
+    /// Number of significant bits.
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            None => 0,
+            Some(top) => 64 * self.limbs.len() as u64 - top.leading_zeros() as u64,
+        }
+    }
+
+    fn add_limbs(&mut self, other: &[u64]) {
+        if self.limbs.len() < other.len() {
+            self.limbs.resize(other.len(), 0);
+        }
+        let mut carry = false;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            if i >= other.len() && !carry {
+                break;
+            }
+            let rhs = other.get(i).copied().unwrap_or(0);
+            let (sum, c1) = limb.overflowing_add(rhs);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        if carry {
+            self.limbs.push(1);
+        }
+    }
+
+    /// Divides in place by a small divisor and returns the remainder.
+    fn div_rem_small(&mut self, divisor: u64) -> u64 {
+        let mut rem = 0u128;
+        for limb in self.limbs.iter_mut().rev() {
+            let cur = (rem << 64) | *limb as u128;
+            *limb = (cur / divisor as u128) as u64;
+            rem = cur % divisor as u128;
+        }
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        rem as u64
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        BigUint::from(value as u128)
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(value: u128) -> Self {
+        let mut limbs = vec![value as u64, (value >> 64) as u64];
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+}
+
+impl PartialEq<u128> for BigUint {
+    fn eq(&self, other: &u128) -> bool {
+        self.to_u128() == Some(*other)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, rhs: &BigUint) {
+        self.add_limbs(&rhs.limbs);
+    }
+}
+
+impl AddAssign<BigUint> for BigUint {
+    fn add_assign(&mut self, rhs: BigUint) {
+        self.add_limbs(&rhs.limbs);
+    }
+}
+
+impl AddAssign<u128> for BigUint {
+    fn add_assign(&mut self, rhs: u128) {
+        self.add_limbs(&BigUint::from(rhs).limbs);
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(mut self, rhs: BigUint) -> BigUint {
+        self += rhs;
+        self
+    }
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: &BigUint) -> BigUint {
+        let mut sum = self.clone();
+        sum += rhs;
+        sum
+    }
+}
+
+impl std::iter::Sum for BigUint {
+    fn sum<I: Iterator<Item = BigUint>>(iter: I) -> Self {
+        iter.fold(BigUint::zero(), |acc, x| acc + x)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        if self.is_zero() {
+            return f.pad_integral(true, "", "0");
+        }
+        let mut rest = self.clone();
+        let mut chunks = Vec::new();
+        while !rest.is_zero() {
+            chunks.push(rest.div_rem_small(CHUNK));
+        }
+        let mut digits = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{chunk:019}"));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigUint;
+
+    #[test]
+    fn test_add_carries_across_limbs() {
+        let mut n = BigUint::from(u128::MAX);
+        n += 1u128;
+        assert_eq!(n.bits(), 129);
+        assert_eq!(n.to_u128(), None);
+        assert_eq!(n.to_string(), "340282366920938463463374607431768211456");
+        n += &BigUint::from(u128::MAX);
+        assert_eq!(n.to_string(), "680564733841876926926749214863536422911");
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(BigUint::from(42u64).to_string(), "42");
+        assert_eq!(
+            BigUint::from(10_000_000_000_000_000_000u128).to_string(),
+            "10000000000000000000"
+        );
+        assert_eq!(format!("{:>5}", BigUint::from(7u64)), "    7");
+    }
+
+    #[test]
+    fn test_ordering_and_equality() {
+        let small = BigUint::from(u64::MAX);
+        let max = BigUint::from(u128::MAX);
+        assert!(small < max);
+        let mut large = max.clone();
+        large += 5u128;
+        assert!(max < large);
+        assert_eq!(small, u64::MAX as u128);
+        assert_eq!(BigUint::zero(), 0);
+    }
+
+    #[test]
+    fn test_sum() {
+        let total: BigUint = (0..4).map(|_| BigUint::from(u128::MAX)).sum();
+        assert_eq!(total.bits(), 130);
+        assert_eq!(
+            total.to_string(),
+            "1361129467683753853853498429727072845820"
+        );
+    }
+}
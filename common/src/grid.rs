@@ -0,0 +1,338 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+pub const NEIGHBORS4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+pub const NEIGHBORS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pos {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Pos {
+    pub fn new(row: usize, col: usize) -> Self {
+        Pos { row, col }
+    }
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GridError {
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {row} has {found} cells, expected {expected} like the first row"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// Dense row-major grid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Builds a grid from rows, rejecting rows whose length differs from the first.
+    pub fn from_rows<I, R>(rows: I) -> Result<Self, GridError>
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoIterator<Item = T>,
+    {
+        let mut cells = Vec::new();
+        let mut width = 0;
+        let mut height = 0;
+        for (row, items) in rows.into_iter().enumerate() {
+            let before = cells.len();
+            cells.extend(items);
+            let found = cells.len() - before;
+            if row == 0 {
+                width = found;
+            } else if found != width {
+                return Err(GridError::RaggedRow {
+                    row,
+                    expected: width,
+                    found,
+                });
+            }
+            height += 1;
+        }
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.row < self.height && pos.col < self.width
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        if self.contains(pos) {
+            Some(&self.cells[pos.row * self.width + pos.col])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.row * self.width + pos.col])
+        } else {
+            None
+        }
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        (0..self.height).map(move |row| self.row(row))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| Pos { row, col }))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Moves `pos` by `(d_row, d_col)`, or `None` if that leaves the grid.
+    pub fn offset(&self, pos: Pos, d_row: isize, d_col: isize) -> Option<Pos> {
+        let row = pos.row.checked_add_signed(d_row)?;
+        let col = pos.col.checked_add_signed(d_col)?;
+        let moved = Pos { row, col };
+        if self.contains(moved) {
+            Some(moved)
+        } else {
+            None
+        }
+    }
+
+    /// Moves `pos` by `(d_row, d_col)` on a torus. The grid must not be empty.
+    pub fn wrapping_offset(&self, pos: Pos, d_row: isize, d_col: isize) -> Pos {
+        let row = (pos.row as isize + d_row).rem_euclid(self.height as isize) as usize;
+        let col = (pos.col as isize + d_col).rem_euclid(self.width as isize) as usize;
+        Pos { row, col }
+    }
+
+    pub fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBORS4
+            .iter()
+            .filter_map(move |&(d_row, d_col)| self.offset(pos, d_row, d_col))
+    }
+
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBORS8
+            .iter()
+            .filter_map(move |&(d_row, d_col)| self.offset(pos, d_row, d_col))
+    }
+
+    /// First position in row-major order holding `value`.
+    pub fn find(&self, value: &T) -> Option<Pos>
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .find(|(_, cell)| *cell == value)
+            .map(|(pos, _)| pos)
+    }
+
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Pos> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter(move |(_, cell)| *cell == value)
+            .map(|(pos, _)| pos)
+    }
+
+    pub fn count(&self, value: &T) -> usize
+    where
+        T: PartialEq,
+    {
+        self.cells.iter().filter(|cell| *cell == value).count()
+    }
+
+    pub fn count_where(&self, mut pred: impl FnMut(&T) -> bool) -> usize {
+        self.cells.iter().filter(|cell| pred(cell)).count()
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        assert!(self.contains(pos), "{pos} is outside the grid");
+        &self.cells[pos.row * self.width + pos.col]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        assert!(self.contains(pos), "{pos} is outside the grid");
+        &mut self.cells[pos.row * self.width + pos.col]
+    }
+}
+
+/// Parses one row per non-blank line.
+impl FromStr for Grid<char> {
+    type Err = GridError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Grid::from_rows(
+            input
+                .lines()
+                .map(|line| line.trim_end_matches('\r'))
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.chars()),
+        )
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Grid, GridError, Pos};
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        let input = "ab.\n.c#\n";
+        let grid: Grid<char> = input.parse().unwrap();
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid[Pos::new(1, 2)], '#');
+        assert_eq!(grid.to_string(), input);
+    }
+
+    #[test]
+    fn test_parse_crlf() {
+        let unix: Grid<char> = "ab.\n.c#\n".parse().unwrap();
+        for input in ["ab.\r\n.c#\r\n", "ab.\r\n\r\n.c#\r", "\r\nab.\r\n.c#"] {
+            let grid: Grid<char> = input.parse().unwrap();
+            assert_eq!(grid, unix, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_ragged_rows() {
+        let err = "...\n..\n".parse::<Grid<char>>().unwrap_err();
+        assert_eq!(
+            err,
+            GridError::RaggedRow {
+                row: 1,
+                expected: 3,
+                found: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_neighbors_stay_in_bounds() {
+        let grid = Grid::new(3, 3, '.');
+        assert_eq!(grid.neighbors4(Pos::new(0, 0)).count(), 2);
+        assert_eq!(grid.neighbors8(Pos::new(0, 0)).count(), 3);
+        assert_eq!(grid.neighbors8(Pos::new(1, 1)).count(), 8);
+        assert_eq!(grid.neighbors4(Pos::new(2, 1)).count(), 3);
+    }
+
+    #[test]
+    fn test_wrapping_offset() {
+        let grid = Grid::new(4, 3, '.');
+        assert_eq!(grid.wrapping_offset(Pos::new(0, 0), -1, -1), Pos::new(2, 3));
+        assert_eq!(grid.wrapping_offset(Pos::new(2, 3), 1, 2), Pos::new(0, 1));
+    }
+
+    #[test]
+    fn test_find_and_count() {
+        let grid: Grid<char> = "..S\n@.@\n".parse().unwrap();
+        assert_eq!(grid.find(&'S'), Some(Pos::new(0, 2)));
+        assert_eq!(grid.find(&'x'), None);
+        assert_eq!(grid.count(&'@'), 2);
+        assert_eq!(
+            grid.find_all(&'@').collect::<Vec<_>>(),
+            vec![Pos::new(1, 0), Pos::new(1, 2)]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+pub type Rgb = [u8; 3];
+
+/// Minimal RGB raster that can be written as PPM or PNG without extra crates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// Paints a `size`x`size` block whose top-left pixel is `(x * size, y * size)`.
+    pub fn fill_cell(&mut self, x: usize, y: usize, size: usize, color: Rgb) {
+        for py in y * size..(y + 1) * size {
+            for px in x * size..(x + 1) * size {
+                self.set(px, py, color);
+            }
+        }
+    }
+
+    pub fn write_ppm(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for pixel in &self.pixels {
+            out.write_all(pixel)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an 8-bit RGB PNG whose image data uses stored (uncompressed) deflate blocks.
+    pub fn write_png(&self, out: &mut impl Write) -> io::Result<()> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "image too large for PNG");
+        let width = u32::try_from(self.width).map_err(|_| too_large())?;
+        let height = u32::try_from(self.height).map_err(|_| too_large())?;
+
+        out.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'])?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        write_chunk(out, b"IHDR", &header)?;
+
+        let mut raw = Vec::with_capacity(self.height * (1 + 3 * self.width));
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            for pixel in row {
+                raw.extend_from_slice(pixel);
+            }
+        }
+        write_chunk(out, b"IDAT", &zlib_stored(&raw))?;
+        write_chunk(out, b"IEND", &[])
+    }
+
+    /// Saves as PPM or PNG depending on the file extension.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        let mut out = BufWriter::new(File::create(path)?);
+        match extension {
+            Some("ppm") => self.write_ppm(&mut out)?,
+            Some("png") => self.write_png(&mut out)?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unsupported image extension for {}", path.display()),
+                ))
+            }
+        }
+        out.flush()
+    }
+}
+
+fn write_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    out.write_all(&crc.to_be_bytes())
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 65_535;
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(u8::from(is_final));
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65_521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % MOD;
+        b = (b + a) % MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32, Image};
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let mut image = Image::new(2, 1, [0, 0, 0]);
+        image.set(1, 0, [255, 128, 1]);
+        let mut out = Vec::new();
+        image.write_ppm(&mut out).unwrap();
+        assert_eq!(out, b"P6\n2 1\n255\n\x00\x00\x00\xff\x80\x01");
+    }
+
+    #[test]
+    fn test_write_png_layout() {
+        let image = Image::new(3, 2, [10, 20, 30]);
+        let mut out = Vec::new();
+        image.write_png(&mut out).unwrap();
+        assert_eq!(&out[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&out[12..16], b"IHDR");
+        assert_eq!(&out[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(
+            &out[out.len() - 12..],
+            b"\x00\x00\x00\x00IEND\xae\x42\x60\x82"
+        );
+    }
+}
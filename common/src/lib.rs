@@ -0,0 +1,5 @@
+pub mod bignum;
+pub mod grid;
+pub mod image;
+pub mod rng;
+pub mod union_find;
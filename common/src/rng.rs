@@ -0,0 +1,60 @@
+/// Seed for generated inputs that only need to be the same on every run.
+pub const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// Marsaglia's xorshift64 with shifts (13, 7, 17). Small and deterministic,
+/// for test data and benchmarks that should not pull in an external crate.
+#[derive(Clone, Debug)]
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    /// Zero is a fixed point of the generator, so the low bit is always set.
+    pub fn new(seed: u64) -> Self {
+        XorShift { state: seed | 1 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// A value in `0..bound`, with the slight bias of taking a remainder.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+impl Default for XorShift {
+    fn default() -> Self {
+        XorShift::new(DEFAULT_SEED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XorShift;
+
+    #[test]
+    fn test_sequence() {
+        let mut rng = XorShift::new(1);
+        assert_eq!(rng.next_u64(), 1_082_269_761);
+        let mut again = XorShift::new(1);
+        again.next_u64();
+        assert_eq!(rng.next_u64(), again.next_u64());
+    }
+
+    #[test]
+    fn test_zero_seed() {
+        let mut rng = XorShift::new(0);
+        assert!((0..100).all(|_| rng.next_u64() != 0));
+    }
+
+    #[test]
+    fn test_below() {
+        let mut rng = XorShift::default();
+        assert!((0..1000).all(|_| rng.below(7) < 7));
+    }
+}
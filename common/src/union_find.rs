@@ -0,0 +1,327 @@
+/// Disjoint sets over `0..n` with union by size and path compression.
+///
+/// Every set also keeps its members on a circular list threaded through
+/// `next`, so `members` walks a set in time proportional to its size.
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    next: Vec<usize>,
+    components: usize,
+}
+
+impl UnionFind {
+    pub fn new(n: usize) -> Self {
+        UnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            next: (0..n).collect(),
+            components: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Number of disjoint sets.
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    /// Representative of the set containing `x`. Compresses the path in a
+    /// second pass rather than by recursion, so long chains can't overflow
+    /// the stack.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut node = x;
+        while self.parent[node] != root {
+            node = std::mem::replace(&mut self.parent[node], root);
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns `false` if they were
+    /// already the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return false;
+        }
+        let (big, small) = if self.size[ra] < self.size[rb] {
+            (rb, ra)
+        } else {
+            (ra, rb)
+        };
+        self.parent[small] = big;
+        self.size[big] += self.size[small];
+        self.next.swap(big, small);
+        self.components -= 1;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Size of the set containing `x`.
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// Size of every set, ordered by representative.
+    pub fn component_sizes(&self) -> Vec<usize> {
+        roots(&self.parent).map(|r| self.size[r]).collect()
+    }
+
+    /// Members of the set containing `x`, starting with `x`.
+    pub fn members(&self, x: usize) -> Members<'_> {
+        Members::new(&self.next, x)
+    }
+
+    /// Every set as a list of members, sets ordered by their smallest member
+    /// and members in increasing order.
+    pub fn sets(&self) -> Vec<Vec<usize>> {
+        sets(&self.parent, &self.next)
+    }
+}
+
+/// Disjoint sets whose unions can be undone, for offline algorithms that
+/// explore and backtrack. Without path compression `find` is `O(log n)`
+/// through union by size alone, and every union is a constant-size change.
+#[derive(Clone, Debug)]
+pub struct RollbackUnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    next: Vec<usize>,
+    components: usize,
+    /// The root attached by each successful union, `None` for a union of a
+    /// set with itself, so snapshots count every call.
+    history: Vec<Option<usize>>,
+}
+
+impl RollbackUnionFind {
+    pub fn new(n: usize) -> Self {
+        RollbackUnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            next: (0..n).collect(),
+            components: n,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    pub fn find(&self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            x = self.parent[x];
+        }
+        x
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            self.history.push(None);
+            return false;
+        }
+        let (big, small) = if self.size[ra] < self.size[rb] {
+            (rb, ra)
+        } else {
+            (ra, rb)
+        };
+        self.parent[small] = big;
+        self.size[big] += self.size[small];
+        self.next.swap(big, small);
+        self.components -= 1;
+        self.history.push(Some(small));
+        true
+    }
+
+    pub fn same(&self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    pub fn size_of(&self, x: usize) -> usize {
+        self.size[self.find(x)]
+    }
+
+    pub fn component_sizes(&self) -> Vec<usize> {
+        roots(&self.parent).map(|r| self.size[r]).collect()
+    }
+
+    pub fn members(&self, x: usize) -> Members<'_> {
+        Members::new(&self.next, x)
+    }
+
+    pub fn sets(&self) -> Vec<Vec<usize>> {
+        sets(&self.parent, &self.next)
+    }
+
+    /// A point to return to with `rollback`.
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undoes the most recent `union` call. Returns `false` if there was
+    /// none.
+    pub fn undo(&mut self) -> bool {
+        let Some(entry) = self.history.pop() else {
+            return false;
+        };
+        if let Some(small) = entry {
+            let big = self.parent[small];
+            self.parent[small] = small;
+            self.size[big] -= self.size[small];
+            self.next.swap(big, small);
+            self.components += 1;
+        }
+        true
+    }
+
+    /// Undoes every `union` made since `snapshot` was taken.
+    pub fn rollback(&mut self, snapshot: usize) {
+        while self.history.len() > snapshot {
+            self.undo();
+        }
+    }
+}
+
+fn roots(parent: &[usize]) -> impl Iterator<Item = usize> + '_ {
+    (0..parent.len()).filter(move |&x| parent[x] == x)
+}
+
+fn sets(parent: &[usize], next: &[usize]) -> Vec<Vec<usize>> {
+    let mut sets: Vec<Vec<usize>> = roots(parent)
+        .map(|r| {
+            let mut members: Vec<usize> = Members::new(next, r).collect();
+            members.sort_unstable();
+            members
+        })
+        .collect();
+    sets.sort_unstable_by_key(|members| members[0]);
+    sets
+}
+
+/// Walks the circular member list of one set.
+pub struct Members<'a> {
+    next: &'a [usize],
+    start: usize,
+    current: Option<usize>,
+}
+
+impl<'a> Members<'a> {
+    fn new(next: &'a [usize], start: usize) -> Self {
+        Members {
+            next,
+            start,
+            current: Some(start),
+        }
+    }
+}
+
+impl Iterator for Members<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let x = self.current?;
+        let following = self.next[x];
+        self.current = (following != self.start).then_some(following);
+        Some(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RollbackUnionFind, UnionFind};
+
+    #[test]
+    fn test_union_and_members() {
+        let mut uf = UnionFind::new(6);
+        assert!(uf.union(0, 1));
+        assert!(uf.union(2, 3));
+        assert!(uf.union(1, 3));
+        assert!(!uf.union(0, 2));
+        assert_eq!(uf.components(), 3);
+        assert!(uf.same(0, 3));
+        assert!(!uf.same(0, 4));
+        assert_eq!(uf.size_of(2), 4);
+        let mut sizes = uf.component_sizes();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![1, 1, 4]);
+        let mut members: Vec<usize> = uf.members(3).collect();
+        assert_eq!(members[0], 3);
+        members.sort_unstable();
+        assert_eq!(members, vec![0, 1, 2, 3]);
+        assert_eq!(uf.members(5).collect::<Vec<_>>(), vec![5]);
+        assert_eq!(uf.sets(), vec![vec![0, 1, 2, 3], vec![4], vec![5]]);
+    }
+
+    #[test]
+    fn test_long_chain() {
+        // Union by size keeps trees shallow, so the chain is built by hand:
+        // every node's parent is the next one and the root is the last.
+        let n = 1_000_000;
+        let mut uf = UnionFind {
+            parent: (1..n).chain([n - 1]).collect(),
+            size: (1..=n).collect(),
+            next: (1..n).chain([0]).collect(),
+            components: 1,
+        };
+        assert_eq!(uf.find(0), n - 1);
+        assert!(uf.parent.iter().all(|&p| p == n - 1));
+        assert_eq!(uf.size_of(n / 2), n);
+        assert_eq!(uf.members(n / 2).count(), n);
+
+        let uf = RollbackUnionFind {
+            parent: (1..n).chain([n - 1]).collect(),
+            size: (1..=n).collect(),
+            next: (1..n).chain([0]).collect(),
+            components: 1,
+            history: Vec::new(),
+        };
+        assert_eq!(uf.find(0), n - 1);
+        assert!(uf.same(0, n / 2));
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut uf = RollbackUnionFind::new(5);
+        uf.union(0, 1);
+        let snapshot = uf.snapshot();
+        uf.union(2, 3);
+        uf.union(1, 0);
+        uf.union(3, 1);
+        assert_eq!(uf.components(), 2);
+        assert_eq!(uf.sets(), vec![vec![0, 1, 2, 3], vec![4]]);
+
+        assert!(uf.undo());
+        assert_eq!(uf.sets(), vec![vec![0, 1], vec![2, 3], vec![4]]);
+        uf.rollback(snapshot);
+        assert_eq!(uf.components(), 4);
+        assert_eq!(uf.sets(), vec![vec![0, 1], vec![2], vec![3], vec![4]]);
+        assert_eq!(uf.members(2).collect::<Vec<_>>(), vec![2]);
+        uf.rollback(0);
+        assert_eq!(uf.components(), 5);
+        assert!(!uf.undo());
+    }
+}
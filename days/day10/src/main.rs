@@ -114,6 +114,7 @@ fn parse_machine(line: &str) -> (Vec<bool>, Vec<Vec<usize>>, Vec<usize>) {
     (target, buttons, joltages)
 }
 
+#[allow(clippy::needless_range_loop)]
 fn find_min_solution_mod2(a: &[Vec<u8>], b: &[u8], free_vars: &[usize], partial: &mut Vec<u8>, best: &mut Option<usize>) {
     if free_vars.is_empty() {
         let mut solution = partial.clone();
@@ -157,6 +158,7 @@ fn find_min_solution_mod2(a: &[Vec<u8>], b: &[u8], free_vars: &[usize], partial:
     partial[var] = 0;
 }
 
+#[allow(clippy::needless_range_loop)]
 fn solve_system_mod2(a: &mut [Vec<u8>], b: &mut [u8]) -> Option<usize> {
     let n = a.len();
     let m = a[0].len();
@@ -176,9 +178,7 @@ fn solve_system_mod2(a: &mut [Vec<u8>], b: &mut [u8]) -> Option<usize> {
         if let Some(p) = pivot {
             if p != row {
                 a.swap(row, p);
-                let temp = b[row];
-                b[row] = b[p];
-                b[p] = temp;
+                b.swap(row, p);
             }
             
             for r in (row + 1)..n {
@@ -235,6 +235,7 @@ fn solve_machine(target: &[bool], buttons: &[Vec<usize>]) -> Option<usize> {
     solve_system_mod2(&mut a, &mut b)
 }
 
+#[allow(clippy::needless_range_loop)]
 fn gaussian_rref(a: &[Vec<i64>], b: &[i64]) -> Option<(Vec<Vec<Fraction>>, Vec<usize>)> {
     let n = a.len();
     let m = a[0].len();
@@ -309,6 +310,7 @@ fn solve_machine_joltage(joltages: &[usize], buttons: &[Vec<usize>]) -> Option<u
     }
     let mut best: Option<i64> = None;
     let mut free_values = vec![0i64; k];
+    #[allow(clippy::needless_range_loop)]
     fn dfs(
         idx: usize,
         free_cols: &[usize],
@@ -375,7 +377,7 @@ fn solve_machine_joltage(joltages: &[usize], buttons: &[Vec<usize>]) -> Option<u
         let ub = free_bounds[col_idx] as i64;
         for val in 0..=ub {
             if let Some(b) = *best {
-                if (free_values.iter().take(idx).map(|&v| v).sum::<i64>() + val) >= b {
+                if (free_values.iter().take(idx).copied().sum::<i64>() + val) >= b {
                     break;
                 }
             }
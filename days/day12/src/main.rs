@@ -37,9 +37,9 @@ fn normalize(grid: &[Vec<bool>]) -> Vec<Vec<bool>> {
     let w = grid[0].len();
     let mut top = h;
     let mut left = w;
-    for y in 0..h {
-        for x in 0..w {
-            if grid[y][x] {
+    for (y, row) in grid.iter().enumerate() {
+        for (x, &cell) in row.iter().enumerate() {
+            if cell {
                 if y < top {
                     top = y;
                 }
@@ -78,10 +78,10 @@ fn unique_orientations(grid: &[Vec<bool>]) -> Vec<Orientation> {
                 let w = norm[0].len();
                 let mut rows = Vec::with_capacity(h);
                 let mut area = 0usize;
-                for y in 0..h {
+                for row in &norm {
                     let mut mask = 0u64;
-                    for x in 0..w {
-                        if norm[y][x] {
+                    for (x, &cell) in row.iter().enumerate() {
+                        if cell {
                             mask |= 1u64 << x;
                             area += 1;
                         }
@@ -96,7 +96,9 @@ fn unique_orientations(grid: &[Vec<bool>]) -> Vec<Orientation> {
     result
 }
 
-fn parse_input(input: &str) -> (Vec<Shape>, Vec<(usize, usize, Vec<usize>)>) {
+type Region = (usize, usize, Vec<usize>);
+
+fn parse_input(input: &str) -> (Vec<Shape>, Vec<Region>) {
     let mut lines = input.lines().peekable();
     let mut shapes = Vec::new();
     while let Some(line) = lines.peek() {
@@ -115,7 +117,7 @@ fn parse_input(input: &str) -> (Vec<Shape>, Vec<(usize, usize, Vec<usize>)>) {
             if l.is_empty() {
                 break;
             }
-            if l.chars().all(|c| c.is_digit(10) || c == 'x' || c == ':' || c == ' ') {
+            if l.chars().all(|c| c.is_ascii_digit() || c == 'x' || c == ':' || c == ' ') {
                 break;
             }
             grid_lines.push(l.to_string());
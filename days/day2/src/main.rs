@@ -4,12 +4,12 @@ fn is_invalid_id_part1(id: u64) -> bool {
     let s = id.to_string();
     let len = s.len();
     
-    if len % 2 != 0 {
+    if !len.is_multiple_of(2) {
         return false;
     }
     
     let half = len / 2;
-    &s[..half] == &s[half..]
+    s[..half] == s[half..]
 }
 
 fn is_invalid_id_part2(id: u64) -> bool {
@@ -21,7 +21,7 @@ fn is_invalid_id_part2(id: u64) -> bool {
     }
     
     for pattern_len in 1..=len / 2 {
-        if len % pattern_len != 0 {
+        if !len.is_multiple_of(pattern_len) {
             continue;
         }
         
@@ -12,8 +12,8 @@ fn max_joltage(bank: &str) -> u64 {
     
     for i in 0..len - 1 {
         let d1 = chars[i].to_digit(10).expect("Expected digit");
-        for j in (i + 1)..len {
-            let d2 = chars[j].to_digit(10).expect("Expected digit");
+        for &c in &chars[i + 1..] {
+            let d2 = c.to_digit(10).expect("Expected digit");
             let joltage = (d1 * 10 + d2) as u64;
             if joltage > max_joltage {
                 max_joltage = joltage;
@@ -55,8 +55,8 @@ fn max_joltage_part2(bank: &str) -> u64 {
         let mut max_digit = 0u32;
         let mut max_idx = start_idx;
         
-        for i in start_idx..end_idx {
-            let digit = chars[i].to_digit(10).expect("Expected digit");
+        for (i, c) in chars.iter().enumerate().take(end_idx).skip(start_idx) {
+            let digit = c.to_digit(10).expect("Expected digit");
             if digit > max_digit {
                 max_digit = digit;
                 max_idx = i;
@@ -0,0 +1,242 @@
+use crate::simulator::{Neighborhood, Rule};
+use common::grid::Grid;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Char,
+    Bits,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "char" => Ok(Backend::Char),
+            "bits" => Ok(Backend::Bits),
+            _ => Err(format!("unknown backend '{s}', expected 'char' or 'bits'")),
+        }
+    }
+}
+
+/// Rolls stored as one bit per cell, 64 columns per word; bit `i` of word
+/// `k` in a row is column `64 * k + i`. Bits past the grid width stay zero.
+pub struct BitGrid {
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+    neighborhood: Neighborhood,
+    threshold: usize,
+}
+
+impl BitGrid {
+    pub fn supports(rule: &Rule) -> bool {
+        rule.radius == 1 && !rule.wrap
+    }
+
+    pub fn new(grid: &Grid<char>, rule: Rule) -> Result<Self, String> {
+        if !Self::supports(&rule) {
+            return Err("the bits backend only supports radius 1 without --wrap".to_string());
+        }
+        let words_per_row = grid.width().div_ceil(64);
+        let mut words = vec![0u64; words_per_row * grid.height()];
+        for pos in grid.find_all(&'@') {
+            words[pos.row * words_per_row + pos.col / 64] |= 1 << (pos.col % 64);
+        }
+        Ok(BitGrid {
+            height: grid.height(),
+            words_per_row,
+            words,
+            neighborhood: rule.neighborhood,
+            threshold: rule.threshold,
+        })
+    }
+
+    fn row(&self, row: usize) -> &[u64] {
+        &self.words[row * self.words_per_row..(row + 1) * self.words_per_row]
+    }
+
+    /// Per-word masks of rolls that have fewer than `threshold` neighboring rolls.
+    pub fn accessible(&self) -> Vec<u64> {
+        let empty = vec![0u64; self.words_per_row];
+        let mut masks = vec![0u64; self.words.len()];
+        let mut neighbors = Vec::with_capacity(8);
+
+        for r in 0..self.height {
+            let up = if r > 0 { self.row(r - 1) } else { &empty };
+            let mid = self.row(r);
+            let down = if r + 1 < self.height {
+                self.row(r + 1)
+            } else {
+                &empty
+            };
+
+            for k in 0..self.words_per_row {
+                neighbors.clear();
+                neighbors.push(up[k]);
+                neighbors.push(down[k]);
+                neighbors.push(west(mid, k));
+                neighbors.push(east(mid, k));
+                if self.neighborhood == Neighborhood::Moore {
+                    neighbors.push(west(up, k));
+                    neighbors.push(east(up, k));
+                    neighbors.push(west(down, k));
+                    neighbors.push(east(down, k));
+                }
+
+                let mut planes = [0u64; 4];
+                for &mask in &neighbors {
+                    add_bitsliced(&mut planes, mask);
+                }
+                masks[r * self.words_per_row + k] = mid[k] & less_than(&planes, self.threshold);
+            }
+        }
+
+        masks
+    }
+
+    pub fn count_accessible(&self) -> usize {
+        self.accessible()
+            .iter()
+            .map(|mask| mask.count_ones() as usize)
+            .sum()
+    }
+
+    /// Removes one wave of accessible rolls and returns how many were removed.
+    pub fn step(&mut self) -> usize {
+        let accessible = self.accessible();
+        let mut removed = 0;
+        for (word, mask) in self.words.iter_mut().zip(accessible) {
+            *word &= !mask;
+            removed += mask.count_ones() as usize;
+        }
+        removed
+    }
+
+    pub fn run(&mut self) -> usize {
+        let mut total_removed = 0;
+        loop {
+            let removed = self.step();
+            if removed == 0 {
+                break;
+            }
+            total_removed += removed;
+        }
+        total_removed
+    }
+}
+
+/// Bit `i` of the result is the cell at column `i - 1` of `row`.
+fn west(row: &[u64], k: usize) -> u64 {
+    let carry = if k > 0 { row[k - 1] >> 63 } else { 0 };
+    (row[k] << 1) | carry
+}
+
+/// Bit `i` of the result is the cell at column `i + 1` of `row`.
+fn east(row: &[u64], k: usize) -> u64 {
+    let carry = if k + 1 < row.len() {
+        row[k + 1] << 63
+    } else {
+        0
+    };
+    (row[k] >> 1) | carry
+}
+
+/// Adds a 0/1 mask into a 4-bit counter stored one bit plane per word.
+fn add_bitsliced(planes: &mut [u64; 4], mask: u64) {
+    let mut carry = mask;
+    for plane in planes.iter_mut() {
+        let next = *plane & carry;
+        *plane ^= carry;
+        carry = next;
+    }
+}
+
+/// Lanes whose 4-bit counter is below `threshold`.
+fn less_than(planes: &[u64; 4], threshold: usize) -> u64 {
+    if threshold >= 16 {
+        return u64::MAX;
+    }
+    let mut lt = 0u64;
+    let mut eq = u64::MAX;
+    for bit in (0..4).rev() {
+        let t = if (threshold >> bit) & 1 == 1 {
+            u64::MAX
+        } else {
+            0
+        };
+        lt |= eq & !planes[bit] & t;
+        eq &= !(planes[bit] ^ t);
+    }
+    lt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_bitsliced, less_than, BitGrid};
+    use crate::simulator::{Neighborhood, Rule, Simulator};
+    use common::grid::Grid;
+    use common::rng::XorShift;
+
+    fn wide_grid(width: usize, height: usize) -> Grid<char> {
+        let mut rng = XorShift::default();
+        let mut cells = Vec::with_capacity(height);
+        for _ in 0..height {
+            let row: Vec<char> = (0..width)
+                .map(|_| if rng.below(3) == 0 { '.' } else { '@' })
+                .collect();
+            cells.push(row);
+        }
+        Grid::from_rows(cells).unwrap()
+    }
+
+    #[test]
+    fn test_bitsliced_counter() {
+        for n in 0..=8usize {
+            let mut planes = [0u64; 4];
+            for _ in 0..n {
+                add_bitsliced(&mut planes, 1);
+            }
+            for threshold in 0..=9 {
+                assert_eq!(less_than(&planes, threshold) & 1 == 1, n < threshold);
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_char_backend_across_word_boundaries() {
+        let grid = wide_grid(130, 40);
+        for neighborhood in [Neighborhood::Moore, Neighborhood::VonNeumann] {
+            for threshold in [2, 4, 7] {
+                let rule = Rule {
+                    neighborhood,
+                    threshold,
+                    ..Rule::default()
+                };
+                let bits = BitGrid::new(&grid, rule).unwrap();
+                let chars = Simulator::new(grid.clone(), rule);
+                assert_eq!(bits.count_accessible(), chars.accessible().len());
+
+                let mut bits = BitGrid::new(&grid, rule).unwrap();
+                let mut chars = Simulator::new(grid.clone(), rule);
+                assert_eq!(bits.run(), chars.run().total_removed());
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_unsupported_rules() {
+        let grid = wide_grid(8, 8);
+        let wrap = Rule {
+            wrap: true,
+            ..Rule::default()
+        };
+        assert!(BitGrid::new(&grid, wrap).is_err());
+        let radius = Rule {
+            radius: 2,
+            ..Rule::default()
+        };
+        assert!(BitGrid::new(&grid, radius).is_err());
+    }
+}
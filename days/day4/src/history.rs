@@ -0,0 +1,192 @@
+use common::grid::{Grid, Pos};
+use common::image::{Image, Rgb};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const EMPTY: Rgb = [245, 240, 230];
+const ROLL: Rgb = [110, 75, 40];
+const REMOVED: Rgb = [210, 40, 40];
+
+/// Marks the rolls taken out by the wave a frame belongs to.
+pub const REMOVED_MARK: char = 'x';
+
+/// The starting grid plus the cells removed by each wave, in order.
+#[derive(Clone, Debug)]
+pub struct History {
+    initial: Grid<char>,
+    waves: Vec<Vec<Pos>>,
+}
+
+impl History {
+    pub fn new(initial: Grid<char>) -> Self {
+        History {
+            initial,
+            waves: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, removed: Vec<Pos>) {
+        self.waves.push(removed);
+    }
+
+    pub fn waves(&self) -> &[Vec<Pos>] {
+        &self.waves
+    }
+
+    pub fn total_removed(&self) -> usize {
+        self.waves.iter().map(Vec::len).sum()
+    }
+
+    pub fn count_table(&self) -> String {
+        let mut remaining = self.initial.count(&'@');
+        let mut table = format!("{:>4}  {:>7}  {:>9}\n", "wave", "removed", "remaining");
+        for (wave, removed) in self.waves.iter().enumerate() {
+            remaining -= removed.len();
+            writeln!(
+                table,
+                "{:>4}  {:>7}  {:>9}",
+                wave + 1,
+                removed.len(),
+                remaining
+            )
+            .unwrap();
+        }
+        table
+    }
+
+    /// One frame per wave showing the grid just before that wave with the
+    /// rolls it removes marked, followed by the final grid.
+    pub fn frames(&self) -> Vec<Grid<char>> {
+        let mut grid = self.initial.clone();
+        let mut frames = Vec::with_capacity(self.waves.len() + 1);
+        for removed in &self.waves {
+            let mut frame = grid.clone();
+            for &pos in removed {
+                frame[pos] = REMOVED_MARK;
+                grid[pos] = '.';
+            }
+            frames.push(frame);
+        }
+        frames.push(grid);
+        frames
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameFormat {
+    Text,
+    Ppm,
+    Png,
+}
+
+impl FrameFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            FrameFormat::Text => "txt",
+            FrameFormat::Ppm => "ppm",
+            FrameFormat::Png => "png",
+        }
+    }
+}
+
+impl FromStr for FrameFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(FrameFormat::Text),
+            "ppm" => Ok(FrameFormat::Ppm),
+            "png" => Ok(FrameFormat::Png),
+            _ => Err(format!(
+                "unknown frame format '{s}', expected 'text', 'ppm' or 'png'"
+            )),
+        }
+    }
+}
+
+pub fn frame_image(frame: &Grid<char>, scale: usize) -> Image {
+    let mut image = Image::new(frame.width() * scale, frame.height() * scale, EMPTY);
+    for (pos, &cell) in frame.iter() {
+        let color = match cell {
+            '@' => ROLL,
+            REMOVED_MARK => REMOVED,
+            _ => continue,
+        };
+        image.fill_cell(pos.col, pos.row, scale, color);
+    }
+    image
+}
+
+/// Writes `frame_000.<ext>`, `frame_001.<ext>`, ... into `dir` and returns the paths.
+pub fn write_frames(
+    history: &History,
+    format: FrameFormat,
+    dir: &Path,
+    scale: usize,
+) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let mut paths = Vec::new();
+    for (i, frame) in history.frames().iter().enumerate() {
+        let path = dir.join(format!("frame_{i:03}.{}", format.extension()));
+        match format {
+            FrameFormat::Text => fs::write(&path, frame.to_string())?,
+            FrameFormat::Ppm | FrameFormat::Png => frame_image(frame, scale).save(&path)?,
+        }
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulator::{Rule, Simulator};
+
+    const EXAMPLE: &str = "\
+..@@.@@@@.
+@@@.@.@.@@
+@@@@@.@.@@
+@.@@@@..@.
+@@.@@@@.@@
+.@@@@@@@.@
+.@.@.@.@@@
+@.@@@.@@@@
+.@@@@@@@@.
+@.@.@@@.@.
+";
+
+    #[test]
+    fn test_wave_counts() {
+        let mut sim = Simulator::new(EXAMPLE.parse().unwrap(), Rule::default());
+        let history = sim.run();
+        let counts: Vec<usize> = history.waves().iter().map(Vec::len).collect();
+        assert_eq!(counts, vec![13, 12, 7, 5, 2, 1, 1, 1, 1]);
+        assert_eq!(history.total_removed(), 43);
+    }
+
+    #[test]
+    fn test_count_table() {
+        let mut sim = Simulator::new(EXAMPLE.parse().unwrap(), Rule::default());
+        let table = sim.run().count_table();
+        let mut lines = table.lines();
+        assert_eq!(lines.next(), Some("wave  removed  remaining"));
+        assert_eq!(lines.next(), Some("   1       13         58"));
+        assert_eq!(lines.last(), Some("   9        1         28"));
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut sim = Simulator::new(EXAMPLE.parse().unwrap(), Rule::default());
+        let history = sim.run();
+        let frames = history.frames();
+        assert_eq!(frames.len(), history.waves().len() + 1);
+        assert_eq!(frames[0].count(&'x'), 13);
+        assert_eq!(frames[0].count(&'@'), 71 - 13);
+        assert_eq!(frames[1].count(&'x'), 12);
+        let last = frames.last().unwrap();
+        assert_eq!(last.count(&'x'), 0);
+        assert_eq!(last.count(&'@'), 28);
+    }
+}
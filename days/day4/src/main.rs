@@ -1,140 +1,191 @@
+mod bitgrid;
+mod history;
+mod simulator;
+
+use bitgrid::{Backend, BitGrid};
+use common::grid::{Grid, GridError};
+use history::{write_frames, FrameFormat};
+use simulator::{Rule, Simulator};
 use std::io::{self, Read};
+use std::path::PathBuf;
+use std::time::Instant;
 
-fn count_accessible_rolls(grid: &[Vec<char>]) -> u32 {
-    let rows = grid.len();
-    let cols = if rows > 0 { grid[0].len() } else { 0 };
-    let mut count = 0;
-
-    for i in 0..rows {
-        for j in 0..cols {
-            if grid[i][j] == '@' {
-                let mut adjacent_rolls = 0;
-                
-                for di in -1..=1 {
-                    for dj in -1..=1 {
-                        if di == 0 && dj == 0 {
-                            continue;
-                        }
-                        let ni = i as i32 + di;
-                        let nj = j as i32 + dj;
-                        
-                        if ni >= 0 && ni < rows as i32 && nj >= 0 && nj < cols as i32 {
-                            if grid[ni as usize][nj as usize] == '@' {
-                                adjacent_rolls += 1;
-                            }
-                        }
-                    }
-                }
-                
-                if adjacent_rolls < 4 {
-                    count += 1;
-                }
-            }
-        }
-    }
-    
-    count
+fn parse_grid(input: &str) -> Result<Grid<char>, GridError> {
+    Grid::from_rows(
+        input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::chars),
+    )
 }
 
-fn solve_part1(input: &str) -> u32 {
-    let grid: Vec<Vec<char>> = input
-        .lines()
-        .map(|line| line.trim().chars().collect())
-        .filter(|row: &Vec<char>| !row.is_empty())
-        .collect();
-    
-    count_accessible_rolls(&grid)
+fn solve_part1(input: &str, rule: Rule, backend: Backend) -> Result<u32, GridError> {
+    let grid = parse_grid(input)?;
+    let count = match backend {
+        Backend::Char => Simulator::new(grid, rule).accessible().len(),
+        Backend::Bits => BitGrid::new(&grid, rule)
+            .expect("unsupported rule")
+            .count_accessible(),
+    };
+    Ok(count as u32)
 }
 
-fn find_accessible_positions(grid: &[Vec<char>]) -> Vec<(usize, usize)> {
-    let rows = grid.len();
-    let cols = if rows > 0 { grid[0].len() } else { 0 };
-    let mut accessible = Vec::new();
-
-    for i in 0..rows {
-        for j in 0..cols {
-            if grid[i][j] == '@' {
-                let mut adjacent_rolls = 0;
-                
-                for di in -1..=1 {
-                    for dj in -1..=1 {
-                        if di == 0 && dj == 0 {
-                            continue;
-                        }
-                        let ni = i as i32 + di;
-                        let nj = j as i32 + dj;
-                        
-                        if ni >= 0 && ni < rows as i32 && nj >= 0 && nj < cols as i32 {
-                            if grid[ni as usize][nj as usize] == '@' {
-                                adjacent_rolls += 1;
-                            }
-                        }
-                    }
-                }
-                
-                if adjacent_rolls < 4 {
-                    accessible.push((i, j));
-                }
+fn solve_part2(input: &str, rule: Rule, backend: Backend) -> Result<u32, GridError> {
+    let grid = parse_grid(input)?;
+    let removed = match backend {
+        Backend::Char => Simulator::new(grid, rule).run().total_removed(),
+        Backend::Bits => BitGrid::new(&grid, rule).expect("unsupported rule").run(),
+    };
+    Ok(removed as u32)
+}
+
+fn fail(err: impl std::fmt::Display) -> ! {
+    eprintln!("{err}");
+    std::process::exit(1);
+}
+
+fn flag_value<'a>(flag: &str, value: Option<&'a String>) -> Result<&'a str, String> {
+    value
+        .map(String::as_str)
+        .ok_or_else(|| format!("{flag} needs a value"))
+}
+
+fn parse_count(flag: &str, value: Option<&String>) -> Result<usize, String> {
+    let value = flag_value(flag, value)?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{value}' for {flag}"))
+}
+
+struct Options {
+    rule: Rule,
+    backend: Backend,
+    time: bool,
+    format: FrameFormat,
+    out: Option<PathBuf>,
+    scale: usize,
+}
+
+fn parse_options(command: &str, flags: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        rule: Rule::default(),
+        backend: Backend::Char,
+        time: false,
+        format: FrameFormat::Text,
+        out: None,
+        scale: 4,
+    };
+    let rule = &mut options.rule;
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match (command, flag.as_str()) {
+            (_, "--wrap") => rule.wrap = true,
+            (_, "--neighborhood") => rule.neighborhood = flag_value(flag, flags.next())?.parse()?,
+            (_, "--radius") => rule.radius = parse_count(flag, flags.next())?,
+            (_, "--threshold") => rule.threshold = parse_count(flag, flags.next())?,
+            ("part1" | "part2", "--backend") => {
+                options.backend = flag_value(flag, flags.next())?.parse()?
+            }
+            ("part1" | "part2", "--time") => options.time = true,
+            ("frames", "--format") => options.format = flag_value(flag, flags.next())?.parse()?,
+            ("frames", "--out") => {
+                options.out = Some(PathBuf::from(flag_value(flag, flags.next())?))
             }
+            ("frames", "--scale") => options.scale = parse_count(flag, flags.next())?,
+            _ => return Err(format!("unknown flag '{flag}' for {command}")),
         }
     }
-    
-    accessible
+    if options.rule.radius == 0 {
+        return Err("radius must be at least 1".to_string());
+    }
+    if options.backend == Backend::Bits && !BitGrid::supports(&options.rule) {
+        return Err("the bits backend only supports radius 1 without --wrap".to_string());
+    }
+    if options.scale == 0 {
+        return Err("scale must be at least 1".to_string());
+    }
+    if options.format != FrameFormat::Text && options.out.is_none() {
+        return Err("image frames need an --out directory".to_string());
+    }
+    Ok(options)
 }
 
-fn solve_part2(input: &str) -> u32 {
-    let mut grid: Vec<Vec<char>> = input
-        .lines()
-        .map(|line| line.trim().chars().collect())
-        .filter(|row: &Vec<char>| !row.is_empty())
-        .collect();
-    
-    let mut total_removed = 0;
-    
-    loop {
-        let accessible = find_accessible_positions(&grid);
-        
-        if accessible.is_empty() {
-            break;
-        }
-        
-        for (i, j) in &accessible {
-            grid[*i][*j] = '.';
-        }
-        
-        total_removed += accessible.len() as u32;
-    }
-    
-    total_removed
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {program} <part1|part2|waves|frames> [--neighborhood moore|von-neumann] [--radius N] [--threshold N] [--wrap]");
+    eprintln!("       part1/part2: [--backend char|bits] [--time]");
+    eprintln!("       frames: [--format text|ppm|png] [--out DIR] [--scale N]");
+    eprintln!("Example: {program} part1 < input.txt");
+    std::process::exit(1);
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 || (args[1] != "part1" && args[1] != "part2") {
-        eprintln!("Usage: {} <part1|part2>", args[0]);
-        eprintln!("Example: {} part1 < input.txt", args[0]);
-        std::process::exit(1);
+    if args.len() < 2 || !["part1", "part2", "waves", "frames"].contains(&args[1].as_str()) {
+        usage(&args[0]);
     }
-    
-    let part = &args[1];
+
+    let command = args[1].as_str();
+    let options = parse_options(command, &args[2..]).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        usage(&args[0]);
+    });
 
     let mut input = String::new();
     io::stdin()
         .read_to_string(&mut input)
         .expect("failed to read input");
 
-    let answer = if part == "part1" {
-        solve_part1(&input)
-    } else {
-        solve_part2(&input)
-    };
-    
-    println!("{answer}");
+    let started = Instant::now();
+    match command {
+        "part1" => match solve_part1(&input, options.rule, options.backend) {
+            Ok(answer) => println!("{answer}"),
+            Err(err) => fail(err),
+        },
+        "part2" => match solve_part2(&input, options.rule, options.backend) {
+            Ok(answer) => println!("{answer}"),
+            Err(err) => fail(err),
+        },
+        "waves" => {
+            let grid = parse_grid(&input).unwrap_or_else(|err| fail(err));
+            let history = Simulator::new(grid, options.rule).run();
+            print!("{}", history.count_table());
+        }
+        _ => {
+            let grid = parse_grid(&input).unwrap_or_else(|err| fail(err));
+            let history = Simulator::new(grid, options.rule).run();
+            match &options.out {
+                Some(dir) => {
+                    let paths = write_frames(&history, options.format, dir, options.scale)
+                        .expect("failed to write frames");
+                    println!("wrote {} frames to {}", paths.len(), dir.display());
+                }
+                None => {
+                    let waves = history.waves();
+                    for (i, frame) in history.frames().iter().enumerate() {
+                        match waves.get(i) {
+                            Some(removed) => println!("wave {}: {} removed", i + 1, removed.len()),
+                            None => println!("final"),
+                        }
+                        println!("{frame}");
+                    }
+                }
+            }
+        }
+    }
+    if options.time {
+        eprintln!("elapsed: {:?}", started.elapsed());
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{solve_part1, solve_part2};
+    use super::{parse_options, solve_part1, solve_part2, Backend, Rule};
+    use common::grid::GridError;
+
+    fn flags(flags: &[&str]) -> Vec<String> {
+        flags.iter().map(|flag| flag.to_string()).collect()
+    }
 
     #[test]
     fn test_example_part1() {
@@ -150,7 +201,8 @@ mod tests {
             .@@@@@@@@.
             @.@.@@@.@.
             ";
-        assert_eq!(solve_part1(input), 13);
+        assert_eq!(solve_part1(input, Rule::default(), Backend::Char), Ok(13));
+        assert_eq!(solve_part1(input, Rule::default(), Backend::Bits), Ok(13));
     }
 
     #[test]
@@ -167,6 +219,43 @@ mod tests {
 .@@@@@@@@.
 @.@.@@@.@.
 ";
-        assert_eq!(solve_part2(input), 43);
+        assert_eq!(solve_part2(input, Rule::default(), Backend::Char), Ok(43));
+        assert_eq!(solve_part2(input, Rule::default(), Backend::Bits), Ok(43));
+    }
+
+    #[test]
+    fn test_ragged_grid_is_an_error() {
+        let err = GridError::RaggedRow {
+            row: 1,
+            expected: 3,
+            found: 2,
+        };
+        assert_eq!(
+            solve_part1("@.@\n@@\n", Rule::default(), Backend::Char),
+            Err(err.clone())
+        );
+        assert_eq!(
+            solve_part2("@.@\n@@\n", Rule::default(), Backend::Bits),
+            Err(err)
+        );
+    }
+
+    #[test]
+    fn test_frame_flags_only_apply_to_frames() {
+        let frame_flags = flags(&["--out", "frames", "--format", "ppm", "--scale", "2"]);
+        assert!(parse_options("frames", &frame_flags).is_ok());
+        for command in ["part1", "part2", "waves"] {
+            assert!(parse_options(command, &frame_flags).is_err());
+            assert!(parse_options(command, &flags(&["--radius", "2", "--wrap"])).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_backend_flags_only_apply_to_the_solvers() {
+        let backend_flags = flags(&["--backend", "bits", "--time"]);
+        assert!(parse_options("part1", &backend_flags).is_ok());
+        assert!(parse_options("part2", &backend_flags).is_ok());
+        assert!(parse_options("waves", &backend_flags).is_err());
+        assert!(parse_options("frames", &backend_flags).is_err());
     }
 }
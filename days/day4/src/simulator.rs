@@ -0,0 +1,246 @@
+use crate::history::History;
+use common::grid::{Grid, Pos};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    Moore,
+    VonNeumann,
+}
+
+impl FromStr for Neighborhood {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "moore" => Ok(Neighborhood::Moore),
+            "von-neumann" => Ok(Neighborhood::VonNeumann),
+            _ => Err(format!(
+                "unknown neighborhood '{s}', expected 'moore' or 'von-neumann'"
+            )),
+        }
+    }
+}
+
+/// A roll is accessible when fewer than `threshold` rolls lie in its neighborhood.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub neighborhood: Neighborhood,
+    pub radius: usize,
+    pub threshold: usize,
+    pub wrap: bool,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule {
+            neighborhood: Neighborhood::Moore,
+            radius: 1,
+            threshold: 4,
+            wrap: false,
+        }
+    }
+}
+
+impl Rule {
+    pub fn offsets(&self) -> Vec<(isize, isize)> {
+        let r = self.radius as isize;
+        let mut offsets = Vec::new();
+        for d_row in -r..=r {
+            for d_col in -r..=r {
+                if d_row == 0 && d_col == 0 {
+                    continue;
+                }
+                let inside = match self.neighborhood {
+                    Neighborhood::Moore => true,
+                    Neighborhood::VonNeumann => d_row.abs() + d_col.abs() <= r,
+                };
+                if inside {
+                    offsets.push((d_row, d_col));
+                }
+            }
+        }
+        offsets
+    }
+}
+
+pub struct Simulator {
+    grid: Grid<char>,
+    rule: Rule,
+    offsets: Vec<(isize, isize)>,
+}
+
+impl Simulator {
+    pub fn new(grid: Grid<char>, rule: Rule) -> Self {
+        let offsets = rule.offsets();
+        Simulator {
+            grid,
+            rule,
+            offsets,
+        }
+    }
+
+    pub fn adjacent_rolls(&self, pos: Pos) -> usize {
+        if !self.rule.wrap {
+            return self
+                .offsets
+                .iter()
+                .filter_map(|&(d_row, d_col)| self.grid.offset(pos, d_row, d_col))
+                .filter(|&n| self.grid[n] == '@')
+                .count();
+        }
+
+        // On a small torus several offsets can land on the same cell (or on
+        // `pos` itself); each distinct neighbor is only counted once.
+        let mut neighbors: Vec<Pos> = self
+            .offsets
+            .iter()
+            .map(|&(d_row, d_col)| self.grid.wrapping_offset(pos, d_row, d_col))
+            .filter(|&n| n != pos)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+            .into_iter()
+            .filter(|&n| self.grid[n] == '@')
+            .count()
+    }
+
+    pub fn accessible(&self) -> Vec<Pos> {
+        self.grid
+            .find_all(&'@')
+            .filter(|&pos| self.adjacent_rolls(pos) < self.rule.threshold)
+            .collect()
+    }
+
+    /// Removes every currently accessible roll at once and returns them.
+    pub fn step(&mut self) -> Vec<Pos> {
+        let accessible = self.accessible();
+        for &pos in &accessible {
+            self.grid[pos] = '.';
+        }
+        accessible
+    }
+
+    /// Keeps removing waves until nothing is accessible, recording each one.
+    pub fn run(&mut self) -> History {
+        let mut history = History::new(self.grid.clone());
+        loop {
+            let removed = self.step();
+            if removed.is_empty() {
+                break;
+            }
+            history.record(removed);
+        }
+        history
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Neighborhood, Rule, Simulator};
+    use common::grid::{Grid, Pos};
+
+    fn grid(input: &str) -> Grid<char> {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn test_offsets() {
+        let moore = Rule::default();
+        assert_eq!(moore.offsets().len(), 8);
+
+        let von_neumann = Rule {
+            neighborhood: Neighborhood::VonNeumann,
+            ..Rule::default()
+        };
+        assert_eq!(von_neumann.offsets().len(), 4);
+
+        let moore2 = Rule {
+            radius: 2,
+            ..Rule::default()
+        };
+        assert_eq!(moore2.offsets().len(), 24);
+
+        let von_neumann2 = Rule {
+            neighborhood: Neighborhood::VonNeumann,
+            radius: 2,
+            ..Rule::default()
+        };
+        assert_eq!(von_neumann2.offsets().len(), 12);
+    }
+
+    #[test]
+    fn test_von_neumann_ignores_diagonals() {
+        let input = "\
+@.@
+.@.
+@.@
+";
+        let rule = Rule {
+            neighborhood: Neighborhood::VonNeumann,
+            threshold: 1,
+            ..Rule::default()
+        };
+        let sim = Simulator::new(grid(input), rule);
+        assert_eq!(sim.adjacent_rolls(Pos::new(1, 1)), 0);
+        assert_eq!(sim.accessible().len(), 5);
+    }
+
+    #[test]
+    fn test_wrap_counts_across_edges() {
+        let input = "\
+@...@
+.....
+@...@
+";
+        let sim = Simulator::new(grid(input), Rule::default());
+        assert_eq!(sim.adjacent_rolls(Pos::new(0, 0)), 0);
+
+        let wrapped = Simulator::new(
+            grid(input),
+            Rule {
+                wrap: true,
+                ..Rule::default()
+            },
+        );
+        assert_eq!(wrapped.adjacent_rolls(Pos::new(0, 0)), 3);
+    }
+
+    #[test]
+    fn test_wrap_counts_each_cell_once() {
+        let sim = Simulator::new(
+            grid("@@\n@@\n"),
+            Rule {
+                wrap: true,
+                ..Rule::default()
+            },
+        );
+        assert_eq!(sim.adjacent_rolls(Pos::new(0, 0)), 3);
+    }
+
+    #[test]
+    fn test_threshold() {
+        let input = "\
+@@@
+@@@
+";
+        let strict = Simulator::new(
+            grid(input),
+            Rule {
+                threshold: 3,
+                ..Rule::default()
+            },
+        );
+        assert_eq!(strict.accessible().len(), 0);
+
+        let mut loose = Simulator::new(
+            grid(input),
+            Rule {
+                threshold: 6,
+                ..Rule::default()
+            },
+        );
+        assert_eq!(loose.run().total_removed(), 6);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::range_set::{Bound, Range};
+use std::fmt;
+
+/// The puzzle input: a block of fresh ID ranges, then optionally a blank
+/// line and a block of ingredient IDs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Database<T> {
+    pub ranges: Vec<Range<T>>,
+    pub ids: Option<Vec<T>>,
+}
+
+/// Parse failures; `line` is 1-based.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    MissingRanges,
+    MissingIds,
+    ExtraSection { line: usize },
+    MalformedRange { line: usize, text: String },
+    InvertedRange { line: usize, text: String },
+    InvalidId { line: usize, text: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingRanges => write!(f, "the database has no fresh ID ranges"),
+            ParseError::MissingIds => write!(
+                f,
+                "the database has no ingredient ID section after the ranges"
+            ),
+            ParseError::ExtraSection { line } => write!(
+                f,
+                "unexpected third section starting on line {line}; \
+                 only one blank line may separate ranges from IDs"
+            ),
+            ParseError::MalformedRange { line, text } => write!(
+                f,
+                "line {line}: expected a range like '3-5', found '{text}'"
+            ),
+            ParseError::InvertedRange { line, text } => {
+                write!(f, "line {line}: range '{text}' starts after it ends")
+            }
+            ParseError::InvalidId { line, text } => {
+                write!(f, "line {line}: invalid ingredient ID '{text}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<T: Bound> Database<T> {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let sections = sections(input);
+        let (ranges, ids) = match sections.as_slice() {
+            [] => return Err(ParseError::MissingRanges),
+            [ranges] => (ranges, None),
+            [ranges, ids] => (ranges, Some(ids)),
+            [_, _, extra, ..] => return Err(ParseError::ExtraSection { line: extra[0].0 }),
+        };
+
+        let ranges = ranges
+            .iter()
+            .map(|&(line, text)| parse_range(line, text))
+            .collect::<Result<_, _>>()?;
+        let ids = ids
+            .map(|ids| {
+                ids.iter()
+                    .map(|&(line, text)| {
+                        text.parse().map_err(|_| ParseError::InvalidId {
+                            line,
+                            text: text.to_string(),
+                        })
+                    })
+                    .collect::<Result<_, _>>()
+            })
+            .transpose()?;
+
+        Ok(Database { ranges, ids })
+    }
+
+    /// The ingredient IDs, for commands that need them.
+    pub fn ids(&self) -> Result<&[T], ParseError> {
+        self.ids.as_deref().ok_or(ParseError::MissingIds)
+    }
+}
+
+/// Groups the trimmed non-blank lines into runs separated by blank lines.
+fn sections(input: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut sections: Vec<Vec<(usize, &str)>> = Vec::new();
+    let mut current = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            if !current.is_empty() {
+                sections.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push((i + 1, line));
+        }
+    }
+    if !current.is_empty() {
+        sections.push(current);
+    }
+    sections
+}
+
+fn parse_range<T: Bound>(line: usize, text: &str) -> Result<Range<T>, ParseError> {
+    let malformed = || ParseError::MalformedRange {
+        line,
+        text: text.to_string(),
+    };
+    // A '-' in the first position is the start's sign, so the separator is
+    // the first '-' after it: "-5--3" is the range -5..=-3.
+    let dash_pos = text
+        .get(1..)
+        .and_then(|rest| rest.find('-'))
+        .ok_or_else(malformed)?
+        + 1;
+    let start: T = text[..dash_pos].trim().parse().map_err(|_| malformed())?;
+    let end: T = text[dash_pos + 1..]
+        .trim()
+        .parse()
+        .map_err(|_| malformed())?;
+    if start > end {
+        return Err(ParseError::InvertedRange {
+            line,
+            text: text.to_string(),
+        });
+    }
+    Ok(Range::new(start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Database, ParseError};
+    use crate::range_set::Range;
+
+    fn parse(input: &str) -> Result<Database<i64>, ParseError> {
+        Database::parse(input)
+    }
+
+    #[test]
+    fn test_parses_both_sections() {
+        let db = parse("\n\n3-5\n-4--2\n\n\n1\n-3\n\n").unwrap();
+        assert_eq!(db.ranges, vec![Range::new(3, 5), Range::new(-4, -2)]);
+        assert_eq!(db.ids, Some(vec![1, -3]));
+    }
+
+    #[test]
+    fn test_missing_sections() {
+        assert_eq!(parse(""), Err(ParseError::MissingRanges));
+        assert_eq!(parse("  \n\n"), Err(ParseError::MissingRanges));
+    }
+
+    #[test]
+    fn test_ids_are_optional() {
+        let db = parse("3-5\n10-14\n").unwrap();
+        assert_eq!(db.ranges, vec![Range::new(3, 5), Range::new(10, 14)]);
+        assert_eq!(db.ids, None);
+        assert_eq!(db.ids(), Err(ParseError::MissingIds));
+    }
+
+    #[test]
+    fn test_stray_blank_line_inside_ranges() {
+        assert_eq!(
+            parse("3-5\n\n10-14\n\n1\n"),
+            Err(ParseError::ExtraSection { line: 5 })
+        );
+        assert_eq!(
+            parse("3-5\n\n10-14\n"),
+            Err(ParseError::InvalidId {
+                line: 3,
+                text: "10-14".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_malformed_ranges() {
+        for bad in ["3", "3-", "-5", "a-5", "3-5-7", "3..5"] {
+            let input = format!("1-2\n{bad}\n\n1\n");
+            assert_eq!(
+                parse(&input),
+                Err(ParseError::MalformedRange {
+                    line: 2,
+                    text: bad.to_string()
+                }),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_inverted_range() {
+        assert_eq!(
+            parse("5-3\n\n1\n"),
+            Err(ParseError::InvertedRange {
+                line: 1,
+                text: "5-3".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_non_numeric_id() {
+        assert_eq!(
+            parse("3-5\n\n1\nseven\n"),
+            Err(ParseError::InvalidId {
+                line: 4,
+                text: "seven".to_string()
+            })
+        );
+        assert_eq!(
+            Database::<u64>::parse("3-5\n\n-1\n"),
+            Err(ParseError::InvalidId {
+                line: 3,
+                text: "-1".to_string()
+            })
+        );
+    }
+}
@@ -1,130 +1,134 @@
+mod database;
+mod query;
+mod range_set;
+mod timeline;
+
+use common::bignum::BigUint;
+use database::{Database, ParseError};
+use query::{count_fresh, coverage, format_coverage};
+use range_set::{Bound, RangeSet};
 use std::io::{self, Read};
+use timeline::{analyze, format_json, format_table, ReportFormat};
+
+fn solve_part1<T: Bound>(input: &str) -> Result<u32, ParseError> {
+    let db = Database::<T>::parse(input)?;
+    let fresh: RangeSet<T> = db.ranges.iter().copied().collect();
 
-#[derive(Clone, Copy)]
-struct Range {
-    start: u64,
-    end: u64,
+    Ok(count_fresh(&fresh, db.ids()?) as u32)
 }
 
-impl Range {
-    fn contains(&self, value: u64) -> bool {
-        value >= self.start && value <= self.end
-    }
+fn solve_part2<T: Bound>(input: &str) -> Result<BigUint, ParseError> {
+    let db = Database::<T>::parse(input)?;
+    let fresh: RangeSet<T> = db.ranges.into_iter().collect();
+    Ok(fresh.len())
 }
 
-fn parse_ranges(input: &str) -> Vec<Range> {
-    let mut ranges = Vec::new();
-    
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            break;
-        }
-        
-        if let Some(dash_pos) = line.find('-') {
-            let start: u64 = line[..dash_pos].parse().expect("Invalid range start");
-            let end: u64 = line[dash_pos + 1..].parse().expect("Invalid range end");
-            ranges.push(Range { start, end });
-        }
-    }
-    
-    ranges
+fn audit<T: Bound>(input: &str) -> Result<String, ParseError> {
+    let db = Database::<T>::parse(input)?;
+    let report = coverage(&db.ranges, db.ids()?);
+    Ok(format_coverage(&db.ranges, &report))
 }
 
-fn parse_ingredient_ids(input: &str) -> Vec<u64> {
-    let mut in_ids_section = false;
-    let mut ids = Vec::new();
-    
-    for line in input.lines() {
-        let line = line.trim();
-        
-        if line.is_empty() {
-            in_ids_section = true;
-            continue;
-        }
-        
-        if in_ids_section {
-            if let Ok(id) = line.parse::<u64>() {
-                ids.push(id);
-            }
-        }
+fn timeline_report<T: Bound>(input: &str, options: &Options) -> Result<String, ParseError> {
+    let db = Database::<T>::parse(input)?;
+    let timeline = analyze(&db.ranges).ok_or(ParseError::MissingRanges)?;
+    Ok(match options.format {
+        ReportFormat::Table => format_table(&timeline, options.top),
+        ReportFormat::Json => format_json(&timeline, options.top),
+    })
+}
+
+fn run<T: Bound>(command: &str, input: &str, options: &Options) -> Result<(), ParseError> {
+    match command {
+        "part1" => println!("{}", solve_part1::<T>(input)?),
+        "part2" => println!("{}", solve_part2::<T>(input)?),
+        "audit" => print!("{}", audit::<T>(input)?),
+        _ => print!("{}", timeline_report::<T>(input, options)?),
     }
-    
-    ids
+    Ok(())
 }
 
-fn is_fresh(id: u64, ranges: &[Range]) -> bool {
-    ranges.iter().any(|range| range.contains(id))
+struct Options {
+    int_type: String,
+    top: usize,
+    format: ReportFormat,
 }
 
-fn solve_part1(input: &str) -> u32 {
-    let ranges = parse_ranges(input);
-    let ingredient_ids = parse_ingredient_ids(input);
-    
-    ingredient_ids.iter()
-        .filter(|&&id| is_fresh(id, &ranges))
-        .count() as u32
+fn flag_value<'a>(flag: &str, value: Option<&'a String>) -> Result<&'a str, String> {
+    value
+        .map(String::as_str)
+        .ok_or_else(|| format!("{flag} needs a value"))
 }
 
-fn merge_ranges(mut ranges: Vec<Range>) -> Vec<Range> {
-    if ranges.is_empty() {
-        return ranges;
-    }
-    
-    ranges.sort_by_key(|r| r.start);
-    
-    let mut merged = Vec::new();
-    let mut current = ranges[0];
-    
-    for range in ranges.into_iter().skip(1) {
-        if range.start <= current.end + 1 {
-            current.end = current.end.max(range.end);
-        } else {
-            merged.push(current);
-            current = range;
+fn parse_options(command: &str, flags: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        int_type: "i128".to_string(),
+        top: 5,
+        format: ReportFormat::Table,
+    };
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match (command, flag.as_str()) {
+            (_, "--int") => options.int_type = flag_value(flag, flags.next())?.to_string(),
+            ("timeline", "--top") => {
+                let value = flag_value(flag, flags.next())?;
+                options.top = value
+                    .parse()
+                    .map_err(|_| format!("invalid --top value '{value}'"))?;
+            }
+            ("timeline", "--format") => options.format = flag_value(flag, flags.next())?.parse()?,
+            _ => return Err(format!("unknown flag '{flag}' for {command}")),
         }
     }
-    merged.push(current);
-    
-    merged
+    if !["i128", "u128"].contains(&options.int_type.as_str()) {
+        return Err(format!(
+            "unknown integer type '{}', expected 'i128' or 'u128'",
+            options.int_type
+        ));
+    }
+    Ok(options)
 }
 
-fn solve_part2(input: &str) -> u64 {
-    let ranges = parse_ranges(input);
-    let merged = merge_ranges(ranges);
-    
-    merged.iter()
-        .map(|range| (range.end - range.start + 1))
-        .sum()
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {program} <part1|part2|audit|timeline> [--int i128|u128]");
+    eprintln!("       timeline: [--top K] [--format table|json]");
+    eprintln!("Example: {program} part1 < input.txt");
+    std::process::exit(1);
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 || (args[1] != "part1" && args[1] != "part2") {
-        eprintln!("Usage: {} <part1|part2>", args[0]);
-        eprintln!("Example: {} part1 < input.txt", args[0]);
-        std::process::exit(1);
+    if args.len() < 2 || !["part1", "part2", "audit", "timeline"].contains(&args[1].as_str()) {
+        usage(&args[0]);
     }
-    
-    let part = &args[1];
+
+    let command = args[1].as_str();
+    let options = parse_options(command, &args[2..]).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        usage(&args[0]);
+    });
 
     let mut input = String::new();
     io::stdin()
         .read_to_string(&mut input)
         .expect("failed to read input");
 
-    let answer = if part == "part1" {
-        solve_part1(&input) as u64
-    } else {
-        solve_part2(&input)
+    let result = match options.int_type.as_str() {
+        "i128" => run::<i128>(command, &input, &options),
+        _ => run::<u128>(command, &input, &options),
     };
-    
-    println!("{answer}");
+    if let Err(err) = result {
+        eprintln!("invalid database: {err}");
+        std::process::exit(1);
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{solve_part1, solve_part2};
+    use super::{parse_options, solve_part1, solve_part2, timeline_report, Options};
+    use crate::database::ParseError;
+    use crate::timeline::ReportFormat;
+    use common::bignum::BigUint;
 
     #[test]
     fn test_example_part1() {
@@ -141,7 +145,7 @@ mod tests {
             17
             32
             ";
-        assert_eq!(solve_part1(input), 3);
+        assert_eq!(solve_part1::<u64>(input).unwrap(), 3);
     }
 
     #[test]
@@ -159,6 +163,94 @@ mod tests {
             17
             32
             ";
-        assert_eq!(solve_part2(input), 14);
+        assert_eq!(solve_part2::<u64>(input).unwrap(), 14);
+    }
+
+    #[test]
+    fn test_negative_bounds() {
+        let input = "\
+            -10--5
+            -3-2
+            4-4
+
+            -11
+            -7
+            -4
+            0
+            4
+            ";
+        assert_eq!(solve_part1::<i64>(input).unwrap(), 3);
+        assert_eq!(solve_part2::<i64>(input).unwrap(), 13);
+    }
+
+    #[test]
+    fn test_ranges_at_unsigned_limits() {
+        let input = "\
+            18446744073709551610-18446744073709551615
+            0-0
+            1-3
+
+            18446744073709551615
+            0
+            ";
+        assert_eq!(solve_part1::<u64>(input).unwrap(), 2);
+        assert_eq!(solve_part2::<u64>(input).unwrap(), 10);
+
+        let full = format!("0-{}\n\n0\n", u128::MAX);
+        let mut expected = BigUint::from(u128::MAX);
+        expected += 1u128;
+        assert_eq!(solve_part2::<u128>(&full).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_ranges_at_signed_limits() {
+        let input = format!(
+            "{}-{}\n{}--1\n\n{}\n{}\n",
+            i128::MIN,
+            i128::MAX,
+            i128::MIN,
+            i128::MIN,
+            i128::MAX
+        );
+        let mut expected = BigUint::from(u128::MAX);
+        expected += 1u128;
+        assert_eq!(solve_part1::<i128>(&input).unwrap(), 2);
+        assert_eq!(solve_part2::<i128>(&input).unwrap(), expected);
+
+        assert_eq!(solve_part2::<i8>("-128-127\n\n0\n").unwrap(), 256);
+        assert_eq!(solve_part2::<i8>("-128--128\n127-127\n\n0\n").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_range_only_input() {
+        let input = "3-5\n10-14\n16-20\n12-18\n";
+        assert_eq!(solve_part2::<u64>(input).unwrap(), 14);
+        let options = Options {
+            int_type: "u64".to_string(),
+            top: 1,
+            format: ReportFormat::Table,
+        };
+        assert!(timeline_report::<u64>(input, &options).is_ok());
+        assert_eq!(solve_part1::<u64>(input), Err(ParseError::MissingIds));
+    }
+
+    #[test]
+    fn test_rejects_malformed_database() {
+        assert!(solve_part1::<u64>("3-5\n\n10-14\n\n1\n").is_err());
+        assert!(solve_part2::<u64>("5-3\n\n1\n").is_err());
+        assert!(solve_part1::<u64>("3-5\n\nabc\n").is_err());
+    }
+
+    #[test]
+    fn test_timeline_flags_only_apply_to_timeline() {
+        let flags: Vec<String> = ["--top", "3", "--format", "json", "--int", "u128"]
+            .iter()
+            .map(|flag| flag.to_string())
+            .collect();
+        assert!(parse_options("timeline", &flags).is_ok());
+        for command in ["part1", "part2", "audit"] {
+            assert!(parse_options(command, &flags).is_err());
+            assert!(parse_options(command, &flags[4..]).is_ok());
+        }
     }
 }
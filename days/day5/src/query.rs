@@ -0,0 +1,147 @@
+use crate::range_set::{Bound, Range, RangeSet};
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+/// Counts the IDs covered by `set` by sorting them and walking the merged
+/// ranges once, instead of searching the ranges for every ID.
+pub fn count_fresh<T: Bound>(set: &RangeSet<T>, ids: &[T]) -> usize {
+    let mut sorted = ids.to_vec();
+    sorted.sort_unstable();
+
+    let mut ranges = set.iter().peekable();
+    let mut count = 0;
+    for id in sorted {
+        while ranges.next_if(|r| r.end < id).is_some() {}
+        match ranges.peek() {
+            Some(range) if range.contains(id) => count += 1,
+            Some(_) => {}
+            None => break,
+        }
+    }
+    count
+}
+
+/// The original ranges (by input index) that cover one ingredient ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Coverage<T> {
+    pub id: T,
+    pub ranges: Vec<usize>,
+}
+
+impl<T> Coverage<T> {
+    pub fn is_fresh(&self) -> bool {
+        !self.ranges.is_empty()
+    }
+}
+
+/// Reports, for every ID in input order, which of the unmerged `ranges`
+/// contain it. IDs and ranges are swept together in sorted order while the
+/// ranges that have started but not yet ended are kept in an ordered set.
+pub fn coverage<T: Bound>(ranges: &[Range<T>], ids: &[T]) -> Vec<Coverage<T>> {
+    let mut by_start: Vec<usize> = (0..ranges.len()).collect();
+    by_start.sort_unstable_by_key(|&i| (ranges[i].start, i));
+    let mut by_id: Vec<usize> = (0..ids.len()).collect();
+    by_id.sort_unstable_by_key(|&i| ids[i]);
+
+    let mut report: Vec<Coverage<T>> = ids
+        .iter()
+        .map(|&id| Coverage {
+            id,
+            ranges: Vec::new(),
+        })
+        .collect();
+    let mut active: BTreeSet<(T, usize)> = BTreeSet::new();
+    let mut next_range = by_start.into_iter().peekable();
+
+    for i in by_id {
+        let id = ids[i];
+        while let Some(r) = next_range.next_if(|&r| ranges[r].start <= id) {
+            active.insert((ranges[r].end, r));
+        }
+        while active.first().is_some_and(|&(end, _)| end < id) {
+            active.pop_first();
+        }
+        let mut covering: Vec<usize> = active.iter().map(|&(_, r)| r).collect();
+        covering.sort_unstable();
+        report[i].ranges = covering;
+    }
+
+    report
+}
+
+/// One line per ID: status, number of covering ranges and the ranges
+/// themselves, numbered by their position in the input.
+pub fn format_coverage<T: Bound>(ranges: &[Range<T>], report: &[Coverage<T>]) -> String {
+    let mut out = String::new();
+    for entry in report {
+        let status = if entry.is_fresh() { "fresh" } else { "spoiled" };
+        let covering: Vec<String> = entry
+            .ranges
+            .iter()
+            .map(|&r| format!("#{} {}-{}", r + 1, ranges[r].start, ranges[r].end))
+            .collect();
+        writeln!(
+            out,
+            "{}\t{status}\t{}\t{}",
+            entry.id,
+            entry.ranges.len(),
+            covering.join(", ")
+        )
+        .unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_fresh, coverage, format_coverage};
+    use crate::range_set::{Range, RangeSet};
+
+    fn example_ranges() -> Vec<Range<u64>> {
+        vec![
+            Range::new(3, 5),
+            Range::new(10, 14),
+            Range::new(16, 20),
+            Range::new(12, 18),
+        ]
+    }
+
+    #[test]
+    fn test_count_fresh_matches_contains() {
+        let set: RangeSet<u64> = example_ranges().into_iter().collect();
+        let ids: Vec<u64> = (0..25).rev().chain([5, 5, 32]).collect();
+        let expected = ids.iter().filter(|&&id| set.contains(id)).count();
+        assert_eq!(count_fresh(&set, &ids), expected);
+        assert_eq!(count_fresh(&set, &[1, 5, 8, 11, 17, 32]), 3);
+        assert_eq!(count_fresh(&RangeSet::new(), &[1, 2]), 0);
+    }
+
+    #[test]
+    fn test_coverage_reports_original_ranges() {
+        let report = coverage(&example_ranges(), &[17, 1, 5, 8, 11, 32, 12]);
+        let covering: Vec<(u64, Vec<usize>)> =
+            report.into_iter().map(|c| (c.id, c.ranges)).collect();
+        assert_eq!(
+            covering,
+            vec![
+                (17, vec![2, 3]),
+                (1, vec![]),
+                (5, vec![0]),
+                (8, vec![]),
+                (11, vec![1]),
+                (32, vec![]),
+                (12, vec![1, 3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_coverage() {
+        let ranges = example_ranges();
+        let report = coverage(&ranges, &[1, 12]);
+        assert_eq!(
+            format_coverage(&ranges, &report),
+            "1\tspoiled\t0\t\n12\tfresh\t2\t#2 10-14, #4 12-18\n"
+        );
+    }
+}
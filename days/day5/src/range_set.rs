@@ -0,0 +1,317 @@
+use common::bignum::BigUint;
+use std::fmt::{Debug, Display};
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+/// Primitive integer types usable as range bounds, signed or unsigned, up to 128 bits.
+pub trait Bound: Copy + Ord + Debug + Display + FromStr<Err = ParseIntError> {
+    const MIN: Self;
+    const MAX: Self;
+
+    fn successor(self) -> Option<Self>;
+
+    fn predecessor(self) -> Option<Self>;
+
+    /// `hi - lo` for `lo <= hi`; always fits because the types are at most 128 bits wide.
+    fn distance(lo: Self, hi: Self) -> u128;
+}
+
+macro_rules! impl_bound {
+    ($($t:ty),*) => {
+        $(
+            impl Bound for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                fn successor(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn predecessor(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn distance(lo: Self, hi: Self) -> u128 {
+                    hi.abs_diff(lo) as u128
+                }
+            }
+        )*
+    };
+}
+
+impl_bound!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Inclusive range `start..=end`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Range<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Bound> Range<T> {
+    pub fn new(start: T, end: T) -> Self {
+        assert!(start <= end, "range start {start} is after end {end}");
+        Range { start, end }
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        value >= self.start && value <= self.end
+    }
+
+    /// Number of values in the range; `2^128` for a full 128-bit range.
+    pub fn len(&self) -> BigUint {
+        let mut len = BigUint::from(T::distance(self.start, self.end));
+        len += 1u128;
+        len
+    }
+}
+
+/// Set of integers stored as sorted, disjoint, non-adjacent inclusive ranges.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RangeSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Bound> RangeSet<T> {
+    pub fn new() -> Self {
+        RangeSet { ranges: Vec::new() }
+    }
+
+    /// Coalesces ranges that are already sorted by start.
+    fn from_sorted(sorted: impl IntoIterator<Item = Range<T>>) -> Self {
+        let mut ranges: Vec<Range<T>> = Vec::new();
+        for range in sorted {
+            match ranges.last_mut() {
+                Some(last) if touches(last, &range) => last.end = last.end.max(range.end),
+                _ => ranges.push(range),
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        // Ranges before `first` end too early to touch `range`; ranges from
+        // `last` on start too late. Everything in between merges with it.
+        let first = self.ranges.partition_point(|r| !touches(r, &range));
+        let last = self.ranges.partition_point(|r| touches(&range, r));
+        let mut merged = range;
+        if first < last {
+            merged.start = merged.start.min(self.ranges[first].start);
+            merged.end = merged.end.max(self.ranges[last - 1].end);
+        }
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        if first >= last {
+            return;
+        }
+        let mut kept = Vec::with_capacity(2);
+        let (head, tail) = (self.ranges[first], self.ranges[last - 1]);
+        if head.start < range.start {
+            kept.push(Range::new(head.start, range.start.predecessor().unwrap()));
+        }
+        if tail.end > range.end {
+            kept.push(Range::new(range.end.successor().unwrap(), tail.end));
+        }
+        self.ranges.splice(first..last, kept);
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let idx = self.ranges.partition_point(|r| r.end < value);
+        self.ranges.get(idx).is_some_and(|r| r.contains(value))
+    }
+
+    pub fn union(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut all: Vec<Range<T>> = self.ranges.iter().chain(&other.ranges).copied().collect();
+        all.sort_unstable();
+        RangeSet::from_sorted(all)
+    }
+
+    pub fn intersection(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (self.ranges[i], other.ranges[j]);
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start <= end {
+                ranges.push(Range::new(start, end));
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    pub fn difference(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for &range in &self.ranges {
+            while j < other.ranges.len() && other.ranges[j].end < range.start {
+                j += 1;
+            }
+            let mut rest = Some(range.start);
+            let mut k = j;
+            while let Some(start) = rest {
+                let Some(cut) = other.ranges.get(k).filter(|o| o.start <= range.end) else {
+                    break;
+                };
+                if cut.start > start {
+                    ranges.push(Range::new(start, cut.start.predecessor().unwrap()));
+                }
+                rest = cut.end.successor().filter(|&next| next <= range.end);
+                k += 1;
+            }
+            if let Some(start) = rest {
+                ranges.push(Range::new(start, range.end));
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    /// Number of integers covered by the set, exact even for a full 128-bit domain.
+    pub fn len(&self) -> BigUint {
+        self.ranges.iter().map(Range::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Range<T>> {
+        self.ranges.iter()
+    }
+}
+
+/// Whether `b` starts no later than the value right after `a` ends.
+fn touches<T: Bound>(a: &Range<T>, b: &Range<T>) -> bool {
+    a.end.successor().is_none_or(|next| b.start <= next)
+}
+
+impl<T: Bound> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut all: Vec<Range<T>> = iter.into_iter().collect();
+        all.sort_unstable();
+        RangeSet::from_sorted(all)
+    }
+}
+
+impl<'a, T: Bound> IntoIterator for &'a RangeSet<T> {
+    type Item = &'a Range<T>;
+    type IntoIter = std::slice::Iter<'a, Range<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bound, Range, RangeSet};
+
+    fn set<T: Bound>(ranges: &[(T, T)]) -> RangeSet<T> {
+        ranges.iter().map(|&(s, e)| Range::new(s, e)).collect()
+    }
+
+    fn pairs<T: Bound>(set: &RangeSet<T>) -> Vec<(T, T)> {
+        set.iter().map(|r| (r.start, r.end)).collect()
+    }
+
+    #[test]
+    fn test_collect_merges_overlapping_and_adjacent() {
+        let s = set(&[(10, 14), (3, 5), (16, 20), (12, 18), (6, 6)]);
+        assert_eq!(pairs(&s), vec![(3, 6), (10, 20)]);
+        assert_eq!(s.len(), 15);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut s = set(&[(1, 2), (10, 12), (20, 25)]);
+        s.insert(Range::new(4, 5));
+        assert_eq!(pairs(&s), vec![(1, 2), (4, 5), (10, 12), (20, 25)]);
+        s.insert(Range::new(3, 3));
+        assert_eq!(pairs(&s), vec![(1, 5), (10, 12), (20, 25)]);
+        s.insert(Range::new(11, 19));
+        assert_eq!(pairs(&s), vec![(1, 5), (10, 25)]);
+        s.insert(Range::new(0, 100));
+        assert_eq!(pairs(&s), vec![(0, 100)]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut s = set(&[(1, 10), (20, 30)]);
+        s.remove(Range::new(4, 6));
+        assert_eq!(pairs(&s), vec![(1, 3), (7, 10), (20, 30)]);
+        s.remove(Range::new(9, 25));
+        assert_eq!(pairs(&s), vec![(1, 3), (7, 8), (26, 30)]);
+        s.remove(Range::new(11, 19));
+        assert_eq!(pairs(&s), vec![(1, 3), (7, 8), (26, 30)]);
+        s.remove(Range::new(0, 40));
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_contains() {
+        let s = set(&[(3, 5), (10, 20)]);
+        let fresh: Vec<u64> = (0..25).filter(|&v| s.contains(v)).collect();
+        let expected: Vec<u64> = (3..=5).chain(10..=20).collect();
+        assert_eq!(fresh, expected);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[(1, 5), (10, 15), (20, 30)]);
+        let b = set(&[(4, 12), (25, 26), (40, 41)]);
+        assert_eq!(pairs(&a.union(&b)), vec![(1, 15), (20, 30), (40, 41)]);
+        assert_eq!(pairs(&a.intersection(&b)), vec![(4, 5), (10, 12), (25, 26)]);
+        assert_eq!(
+            pairs(&a.difference(&b)),
+            vec![(1, 3), (13, 15), (20, 24), (27, 30)]
+        );
+        assert_eq!(pairs(&b.difference(&a)), vec![(6, 9), (40, 41)]);
+    }
+
+    #[test]
+    fn test_edges_at_u64_max() {
+        let mut s = set(&[(u64::MAX - 1, u64::MAX)]);
+        s.insert(Range::new(u64::MAX - 3, u64::MAX - 2));
+        assert_eq!(pairs(&s), vec![(u64::MAX - 3, u64::MAX)]);
+        assert!(s.contains(u64::MAX));
+        let rest = s.difference(&set(&[(u64::MAX - 2, u64::MAX)]));
+        assert_eq!(pairs(&rest), vec![(u64::MAX - 3, u64::MAX - 3)]);
+    }
+
+    #[test]
+    fn test_signed_domain_limits() {
+        let mut s = set(&[(i8::MIN, -1), (0, i8::MAX)]);
+        assert_eq!(pairs(&s), vec![(i8::MIN, i8::MAX)]);
+        assert_eq!(s.len(), 256);
+
+        s.remove(Range::new(i8::MIN, i8::MIN));
+        s.remove(Range::new(i8::MAX, i8::MAX));
+        assert_eq!(pairs(&s), vec![(i8::MIN + 1, i8::MAX - 1)]);
+
+        let full = set(&[(i128::MIN, i128::MAX)]);
+        let ends = set(&[(i128::MIN, i128::MIN), (i128::MAX, i128::MAX)]);
+        let middle = full.difference(&ends);
+        assert_eq!(pairs(&middle), vec![(i128::MIN + 1, i128::MAX - 1)]);
+        assert_eq!(middle.union(&ends), full);
+        assert_eq!(full.len().bits(), 129);
+        assert!(full.contains(i128::MIN) && full.contains(i128::MAX));
+    }
+
+    #[test]
+    fn test_bound_distance() {
+        assert_eq!(<i128 as Bound>::distance(i128::MIN, i128::MAX), u128::MAX);
+        assert_eq!(<u8 as Bound>::distance(3, 250), 247);
+        assert_eq!(<i64 as Bound>::distance(-5, 5), 10);
+        assert_eq!(i128::MAX.successor(), None);
+        assert_eq!(u64::MIN.predecessor(), None);
+    }
+}
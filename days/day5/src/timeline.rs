@@ -0,0 +1,246 @@
+use crate::range_set::{Bound, Range};
+use common::bignum::BigUint;
+use std::cmp::Reverse;
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Table,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(ReportFormat::Table),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(format!("unknown format '{s}', expected 'table' or 'json'")),
+        }
+    }
+}
+
+/// Coverage of the ID line by the unmerged ranges, from the smallest start
+/// to the largest end.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timeline<T> {
+    pub span: Range<T>,
+    pub ranges: usize,
+    pub covered: BigUint,
+    /// Uncovered stretches inside `span`, in increasing order.
+    pub gaps: Vec<Range<T>>,
+    pub max_depth: usize,
+    /// Smallest ID covered by `max_depth` ranges.
+    pub deepest_id: T,
+}
+
+impl<T: Bound> Timeline<T> {
+    pub fn uncovered(&self) -> BigUint {
+        self.gaps.iter().map(Range::len).sum()
+    }
+
+    /// The `k` longest gaps, longest first; equal lengths keep ID order.
+    pub fn largest_gaps(&self, k: usize) -> Vec<Range<T>> {
+        let mut gaps = self.gaps.clone();
+        gaps.sort_by_key(|g| (Reverse(T::distance(g.start, g.end)), g.start));
+        gaps.truncate(k);
+        gaps
+    }
+}
+
+/// Sweeps range starts and ends in ID order, tracking how many ranges cover
+/// the current ID. Starts sort before ends at the same ID, so the depth after
+/// a start is the depth at that ID and ends are never off by one even at
+/// `T::MAX`. Returns `None` when there are no ranges.
+pub fn analyze<T: Bound>(ranges: &[Range<T>]) -> Option<Timeline<T>> {
+    let mut events: Vec<(T, bool)> = ranges
+        .iter()
+        .flat_map(|r| [(r.start, false), (r.end, true)])
+        .collect();
+    events.sort_unstable();
+
+    let (first, last) = (events.first()?.0, events.last()?.0);
+    let mut covered = BigUint::zero();
+    let mut gaps = Vec::new();
+    let mut depth = 0;
+    let mut max_depth = 0;
+    let mut deepest_id = first;
+    // First ID of the current run of covered IDs.
+    let mut run_start = first;
+    let mut last_end: Option<T> = None;
+
+    for (id, is_end) in events {
+        if is_end {
+            depth -= 1;
+            if depth == 0 {
+                covered += &Range::new(run_start, id).len();
+                last_end = Some(id);
+            }
+            continue;
+        }
+        if depth == 0 {
+            if let Some(end) = last_end {
+                if let Some(gap_start) = end.successor().filter(|&s| s < id) {
+                    gaps.push(Range::new(gap_start, id.predecessor().unwrap()));
+                }
+            }
+            run_start = id;
+        }
+        depth += 1;
+        if depth > max_depth {
+            max_depth = depth;
+            deepest_id = id;
+        }
+    }
+
+    Some(Timeline {
+        span: Range::new(first, last),
+        ranges: ranges.len(),
+        covered,
+        gaps,
+        max_depth,
+        deepest_id,
+    })
+}
+
+pub fn format_table<T: Bound>(timeline: &Timeline<T>, top: usize) -> String {
+    let mut out = String::new();
+    let span = timeline.span;
+    writeln!(out, "span       {}-{}", span.start, span.end).unwrap();
+    writeln!(out, "ranges     {}", timeline.ranges).unwrap();
+    writeln!(out, "covered    {}", timeline.covered).unwrap();
+    writeln!(out, "uncovered  {}", timeline.uncovered()).unwrap();
+    writeln!(
+        out,
+        "max depth  {} at {}",
+        timeline.max_depth, timeline.deepest_id
+    )
+    .unwrap();
+
+    let sections = [
+        (
+            format!("gaps ({})", timeline.gaps.len()),
+            timeline.gaps.clone(),
+        ),
+        (format!("largest {top} gaps"), timeline.largest_gaps(top)),
+    ];
+    for (title, gaps) in sections {
+        writeln!(out, "\n{title}").unwrap();
+        writeln!(out, "start\tend\tlength").unwrap();
+        for gap in gaps {
+            writeln!(out, "{}\t{}\t{}", gap.start, gap.end, gap.len()).unwrap();
+        }
+    }
+    out
+}
+
+/// The same report as one JSON object. IDs and lengths are written as bare
+/// integers even when they exceed what a double can hold exactly.
+pub fn format_json<T: Bound>(timeline: &Timeline<T>, top: usize) -> String {
+    fn gap_list<T: Bound>(gaps: &[Range<T>]) -> String {
+        let items: Vec<String> = gaps
+            .iter()
+            .map(|g| {
+                format!(
+                    "{{\"start\": {}, \"end\": {}, \"length\": {}}}",
+                    g.start,
+                    g.end,
+                    g.len()
+                )
+            })
+            .collect();
+        format!("[{}]", items.join(", "))
+    }
+
+    let span = timeline.span;
+    let mut out = String::new();
+    writeln!(out, "{{").unwrap();
+    writeln!(
+        out,
+        "  \"span\": {{\"start\": {}, \"end\": {}}},",
+        span.start, span.end
+    )
+    .unwrap();
+    writeln!(out, "  \"ranges\": {},", timeline.ranges).unwrap();
+    writeln!(out, "  \"covered\": {},", timeline.covered).unwrap();
+    writeln!(out, "  \"uncovered\": {},", timeline.uncovered()).unwrap();
+    writeln!(
+        out,
+        "  \"max_depth\": {{\"depth\": {}, \"id\": {}}},",
+        timeline.max_depth, timeline.deepest_id
+    )
+    .unwrap();
+    writeln!(out, "  \"gaps\": {},", gap_list(&timeline.gaps)).unwrap();
+    writeln!(
+        out,
+        "  \"largest_gaps\": {}",
+        gap_list(&timeline.largest_gaps(top))
+    )
+    .unwrap();
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyze, format_json, format_table};
+    use crate::range_set::{Bound, Range};
+
+    fn ranges<T: Bound>(pairs: &[(T, T)]) -> Vec<Range<T>> {
+        pairs.iter().map(|&(s, e)| Range::new(s, e)).collect()
+    }
+
+    #[test]
+    fn test_example_timeline() {
+        let t = analyze(&ranges(&[(3u64, 5), (10, 14), (16, 20), (12, 18)])).unwrap();
+        assert_eq!(t.span, Range::new(3, 20));
+        assert_eq!(t.covered, 14);
+        assert_eq!(t.gaps, vec![Range::new(6, 9)]);
+        assert_eq!(t.uncovered(), 4);
+        assert_eq!((t.max_depth, t.deepest_id), (2, 12));
+    }
+
+    #[test]
+    fn test_adjacent_and_nested_ranges() {
+        let t = analyze(&ranges(&[(1i64, 10), (11, 12), (2, 8), (3, 4), (4, 4)])).unwrap();
+        assert!(t.gaps.is_empty());
+        assert_eq!(t.covered, 12);
+        assert_eq!((t.max_depth, t.deepest_id), (4, 4));
+        assert!(analyze::<i64>(&[]).is_none());
+    }
+
+    #[test]
+    fn test_largest_gaps() {
+        let t = analyze(&ranges(&[(0u32, 0), (5, 5), (7, 7), (12, 12), (20, 20)])).unwrap();
+        assert_eq!(t.gaps, ranges(&[(1, 4), (6, 6), (8, 11), (13, 19)]));
+        assert_eq!(t.largest_gaps(3), ranges(&[(13, 19), (1, 4), (8, 11)]));
+        assert_eq!(t.largest_gaps(10).len(), 4);
+        assert!(t.largest_gaps(0).is_empty());
+    }
+
+    #[test]
+    fn test_domain_limits() {
+        let t = analyze(&ranges(&[(i128::MIN, i128::MIN), (i128::MAX, i128::MAX)])).unwrap();
+        assert_eq!(t.gaps, vec![Range::new(i128::MIN + 1, i128::MAX - 1)]);
+        assert_eq!(t.covered, 2);
+        assert_eq!(t.uncovered(), u128::MAX - 1);
+
+        let t = analyze(&ranges(&[(0u128, u128::MAX), (u128::MAX, u128::MAX)])).unwrap();
+        assert!(t.gaps.is_empty());
+        assert_eq!(t.covered.bits(), 129);
+        assert_eq!((t.max_depth, t.deepest_id), (2, u128::MAX));
+    }
+
+    #[test]
+    fn test_formats() {
+        let t = analyze(&ranges(&[(3u64, 5), (10, 14)])).unwrap();
+        let table = format_table(&t, 1);
+        assert!(table.contains("max depth  1 at 3\n"));
+        assert!(table.contains("\nlargest 1 gaps\nstart\tend\tlength\n6\t9\t4\n"));
+        let json = format_json(&t, 1);
+        assert!(json.contains("\"span\": {\"start\": 3, \"end\": 14},"));
+        assert!(json.contains("\"largest_gaps\": [{\"start\": 6, \"end\": 9, \"length\": 4}]"));
+    }
+}
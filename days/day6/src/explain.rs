@@ -0,0 +1,70 @@
+use crate::worksheet::{parse_worksheet, worksheet_lines, Problem, Reading, WorksheetError};
+use std::fmt::Write as _;
+
+/// One block per problem: its column span (1-based, inclusive), the source
+/// text cut to those columns, and the expression and subtotal under each
+/// reading. Arithmetic errors are shown in place of the subtotal so the
+/// remaining problems are still listed.
+pub fn explain(input: &str) -> Result<String, WorksheetError> {
+    let problems = parse_worksheet(input)?;
+    let lines = worksheet_lines(input);
+    let mut totals = [Some(0i128), Some(0i128)];
+    let mut out = String::new();
+
+    for (i, problem) in problems.iter().enumerate() {
+        let columns = &problem.columns;
+        writeln!(
+            out,
+            "problem {}: columns {}-{}, operator {}",
+            i + 1,
+            columns.start + 1,
+            columns.end,
+            problem.operator
+        )
+        .unwrap();
+        for line in &lines {
+            let text = line.get(columns.start..columns.end.min(line.len()));
+            writeln!(
+                out,
+                "  |{:<width$}|",
+                text.unwrap_or(""),
+                width = columns.len()
+            )
+            .unwrap();
+        }
+        for (part, reading) in [Reading::Rows, Reading::Columns].into_iter().enumerate() {
+            let result = problem.solve(reading);
+            writeln!(
+                out,
+                "  part {}: {} = {}",
+                part + 1,
+                expression(problem, reading),
+                match &result {
+                    Ok(value) => value.to_string(),
+                    Err(err) => format!("error ({err})"),
+                }
+            )
+            .unwrap();
+            totals[part] = match (totals[part], result) {
+                (Some(total), Ok(value)) => total.checked_add(value),
+                _ => None,
+            };
+        }
+    }
+
+    let total = |t: Option<i128>| t.map_or("error".to_string(), |t| t.to_string());
+    writeln!(
+        out,
+        "grand total: part 1 = {}, part 2 = {}",
+        total(totals[0]),
+        total(totals[1])
+    )
+    .unwrap();
+    Ok(out)
+}
+
+fn expression(problem: &Problem, reading: Reading) -> String {
+    problem
+        .numbers(reading)
+        .join(&format!(" {} ", problem.operator))
+}
@@ -1,165 +1,36 @@
-use std::io::{self, Read};
-
-fn find_problems(lines: &[&str]) -> Vec<(usize, usize)> {
-    if lines.is_empty() {
-        return Vec::new();
-    }
-
-    let max_width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
-    let num_cols = max_width;
-
-    let mut separator_cols = vec![false; num_cols];
-    for col in 0..num_cols {
-        separator_cols[col] = lines.iter().all(|line| {
-            col >= line.len() || line.chars().nth(col).unwrap_or(' ') == ' '
-        });
-    }
+mod explain;
+mod worksheet;
 
-    let mut problems = Vec::new();
-    let mut current_problem_start = None;
-
-    for col in 0..num_cols {
-        if separator_cols[col] {
-            if let Some(start) = current_problem_start {
-                problems.push((start, col));
-                current_problem_start = None;
-            }
-        } else {
-            if current_problem_start.is_none() {
-                current_problem_start = Some(col);
-            }
-        }
-    }
-    if let Some(start) = current_problem_start {
-        problems.push((start, num_cols));
-    }
-
-    problems
+use explain::explain;
+use std::io::{self, Read};
+use worksheet::{parse_worksheet, Reading, WorksheetError};
+
+fn grand_total(input: &str, reading: Reading) -> Result<i128, WorksheetError> {
+    parse_worksheet(input)?
+        .iter()
+        .try_fold(0i128, |total, problem| {
+            total
+                .checked_add(problem.solve(reading)?)
+                .ok_or(WorksheetError::TotalOverflow)
+        })
 }
 
-fn solve_part1(input: &str) -> i64 {
-    let lines: Vec<&str> = input.lines().collect();
-    if lines.is_empty() {
-        return 0;
-    }
-
-    let num_rows = lines.len();
-    let problems = find_problems(&lines);
-
-    let mut grand_total = 0i64;
-    for (start_col, end_col) in problems {
-        let mut numbers = Vec::new();
-        let mut operation = None;
-
-        for row in 0..num_rows {
-            let line = lines[row];
-            let mut num_str = String::new();
-            
-            for col in start_col..end_col.min(line.len()) {
-                if let Some(ch) = line.chars().nth(col) {
-                    if ch.is_ascii_digit() {
-                        num_str.push(ch);
-                    }
-                }
-            }
-
-            if row == num_rows - 1 {
-                for col in start_col..end_col.min(line.len()) {
-                    if let Some(ch) = line.chars().nth(col) {
-                        if ch == '*' || ch == '+' {
-                            operation = Some(ch);
-                            break;
-                        }
-                    }
-                }
-            } else {
-                if !num_str.is_empty() {
-                    if let Ok(num) = num_str.parse::<i64>() {
-                        numbers.push(num);
-                    }
-                }
-            }
-        }
-
-        if let Some(op) = operation {
-            let result = if op == '*' {
-                numbers.iter().product::<i64>()
-            } else {
-                numbers.iter().sum::<i64>()
-            };
-            grand_total += result;
-        }
-    }
-
-    grand_total
+fn solve_part1(input: &str) -> Result<i128, WorksheetError> {
+    grand_total(input, Reading::Rows)
 }
 
-fn solve_part2(input: &str) -> i64 {
-    let lines: Vec<&str> = input.lines().collect();
-    if lines.is_empty() {
-        return 0;
-    }
-
-    let num_rows = lines.len();
-    let problems = find_problems(&lines);
-
-    let mut grand_total = 0i64;
-    for (start_col, end_col) in problems {
-        let mut numbers = Vec::new();
-        let mut operation = None;
-
-        for col in start_col..end_col.min(lines[num_rows - 1].len()) {
-            if let Some(ch) = lines[num_rows - 1].chars().nth(col) {
-                if ch == '*' || ch == '+' {
-                    operation = Some(ch);
-                    break;
-                }
-            }
-        }
-
-        for col in (start_col..end_col).rev() {
-            let mut num_str = String::new();
-            let mut has_digit = false;
-            
-            for row in 0..(num_rows - 1) {
-                if col < lines[row].len() {
-                    if let Some(ch) = lines[row].chars().nth(col) {
-                        if ch.is_ascii_digit() {
-                            num_str.push(ch);
-                            has_digit = true;
-                        }
-                    }
-                }
-            }
-            
-            if has_digit && !num_str.is_empty() {
-                if let Ok(num) = num_str.parse::<i64>() {
-                    numbers.push(num);
-                }
-            }
-        }
-
-        if let Some(op) = operation {
-            let result = if op == '*' {
-                numbers.iter().product::<i64>()
-            } else {
-                numbers.iter().sum::<i64>()
-            };
-            grand_total += result;
-        }
-    }
-
-    grand_total
+fn solve_part2(input: &str) -> Result<i128, WorksheetError> {
+    grand_total(input, Reading::Columns)
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 || (args[1] != "part1" && args[1] != "part2") {
-        eprintln!("Usage: {} <part1|part2>", args[0]);
+    if args.len() != 2 || !["part1", "part2", "explain"].contains(&args[1].as_str()) {
+        eprintln!("Usage: {} <part1|part2|explain>", args[0]);
         eprintln!("Example: {} part1 < input.txt", args[0]);
         std::process::exit(1);
     }
-    
+
     let part = &args[1];
 
     let mut input = String::new();
@@ -167,11 +38,63 @@ fn main() {
         .read_to_string(&mut input)
         .expect("failed to read input");
 
-    let answer = if part == "part1" {
-        solve_part1(&input)
-    } else {
-        solve_part2(&input)
+    let answer = match part.as_str() {
+        "part1" => solve_part1(&input).map(|total| total.to_string()),
+        "part2" => solve_part2(&input).map(|total| total.to_string()),
+        _ => explain(&input).map(|report| report.trim_end().to_string()),
     };
-    
-    println!("{answer}");
+
+    match answer {
+        Ok(output) => println!("{output}"),
+        Err(err) => {
+            eprintln!("invalid worksheet: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve_part1, solve_part2};
+    use crate::explain::explain;
+
+    const EXAMPLE: &str = "\
+123 328  51 64 
+ 45 64  387 23 
+  6 98  215 314
+*   +   *   +  
+";
+
+    #[test]
+    fn test_example_part1() {
+        assert_eq!(solve_part1(EXAMPLE).unwrap(), 4277556);
+    }
+
+    #[test]
+    fn test_example_part2() {
+        assert_eq!(solve_part2(EXAMPLE).unwrap(), 3263827);
+    }
+
+    #[test]
+    fn test_non_commutative_reading_order() {
+        // Rows: 12 - 34 and 20 / 5. Columns, right to left: 24 - 13 and 5 / 2.
+        let input = "12 20\n34  5\n-  / \n";
+        assert_eq!(solve_part1(input).unwrap(), -22 + 4);
+        assert_eq!(solve_part2(input).unwrap(), 11 + 2);
+    }
+
+    #[test]
+    fn test_errors_instead_of_silent_zero() {
+        assert!(solve_part1("").is_err());
+        assert!(solve_part1("1 2\n3 4\n").is_err());
+        assert!(solve_part1("4\n0\n/\n").is_err());
+    }
+
+    #[test]
+    fn test_explain_lists_each_problem() {
+        let report = explain(EXAMPLE).unwrap();
+        assert!(report.starts_with("problem 1: columns 1-3, operator *\n  |123|\n"));
+        assert!(report.contains("  part 2: 4 + 431 + 623 = 1058\n"));
+        assert!(report.ends_with("grand total: part 1 = 4277556, part 2 = 3263827\n"));
+    }
 }
@@ -0,0 +1,503 @@
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Min,
+    Max,
+}
+
+impl FromStr for Operator {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "+" => Ok(Operator::Add),
+            "-" => Ok(Operator::Subtract),
+            "*" => Ok(Operator::Multiply),
+            "/" => Ok(Operator::Divide),
+            "^" => Ok(Operator::Power),
+            "min" => Ok(Operator::Min),
+            "max" => Ok(Operator::Max),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Power => "^",
+            Operator::Min => "min",
+            Operator::Max => "max",
+        };
+        f.write_str(symbol)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArithmeticError {
+    NoOperands,
+    Overflow,
+    DivisionByZero,
+}
+
+impl fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArithmeticError::NoOperands => write!(f, "problem has no operands"),
+            ArithmeticError::Overflow => write!(f, "result overflows i128"),
+            ArithmeticError::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+impl Operator {
+    /// Folds the operands left to right in the order the reading produced
+    /// them, so `-`, `/` and `^` compute `((a op b) op c) ...`. Division
+    /// truncates toward zero.
+    pub fn apply(self, operands: &[i128]) -> Result<i128, ArithmeticError> {
+        let (&first, rest) = operands.split_first().ok_or(ArithmeticError::NoOperands)?;
+        rest.iter().try_fold(first, |acc, &x| match self {
+            Operator::Add => acc.checked_add(x).ok_or(ArithmeticError::Overflow),
+            Operator::Subtract => acc.checked_sub(x).ok_or(ArithmeticError::Overflow),
+            Operator::Multiply => acc.checked_mul(x).ok_or(ArithmeticError::Overflow),
+            Operator::Divide if x == 0 => Err(ArithmeticError::DivisionByZero),
+            Operator::Divide => acc.checked_div(x).ok_or(ArithmeticError::Overflow),
+            Operator::Power => checked_pow(acc, x),
+            Operator::Min => Ok(acc.min(x)),
+            Operator::Max => Ok(acc.max(x)),
+        })
+    }
+}
+
+/// `base^exp` for a non-negative exponent. Exponents too large for `u32`
+/// only fit when the base is -1, 0 or 1.
+fn checked_pow(base: i128, exp: i128) -> Result<i128, ArithmeticError> {
+    if exp < 0 {
+        return Err(ArithmeticError::Overflow);
+    }
+    match u32::try_from(exp) {
+        Ok(exp) => base.checked_pow(exp).ok_or(ArithmeticError::Overflow),
+        Err(_) => match base {
+            0 | 1 => Ok(base),
+            -1 => Ok(if exp % 2 == 0 { 1 } else { -1 }),
+            _ => Err(ArithmeticError::Overflow),
+        },
+    }
+}
+
+/// How the digits of a problem are grouped into numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reading {
+    /// Part 1: each operand row is one number.
+    Rows,
+    /// Part 2: each column is one number, read top to bottom, and the
+    /// columns are taken from right to left.
+    Columns,
+}
+
+/// Worksheet errors; `line` and `col` are 1-based.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WorksheetError {
+    Empty,
+    Tab {
+        line: usize,
+        col: usize,
+    },
+    MissingOperatorRow {
+        line: usize,
+    },
+    UnexpectedChar {
+        line: usize,
+        col: usize,
+        ch: char,
+    },
+    MissingOperator {
+        columns: Range<usize>,
+    },
+    UnknownOperator {
+        columns: Range<usize>,
+        token: String,
+    },
+    MultipleOperators {
+        columns: Range<usize>,
+        tokens: Vec<String>,
+    },
+    OperandTooLarge {
+        columns: Range<usize>,
+        digits: String,
+    },
+    Arithmetic {
+        columns: Range<usize>,
+        error: ArithmeticError,
+    },
+    TotalOverflow,
+}
+
+impl fmt::Display for WorksheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorksheetError::Empty => write!(f, "the worksheet is empty"),
+            WorksheetError::Tab { line, col } => write!(
+                f,
+                "line {line}, column {col}: tabs are not allowed because \
+                 they make the column alignment ambiguous"
+            ),
+            WorksheetError::MissingOperatorRow { line } => write!(
+                f,
+                "line {line} holds only numbers; the last line must hold the operators"
+            ),
+            WorksheetError::UnexpectedChar { line, col, ch } => {
+                write!(f, "line {line}, column {col}: unexpected character {ch:?}")
+            }
+            WorksheetError::MissingOperator { columns } => write!(
+                f,
+                "columns {}-{}: problem has no operator",
+                columns.start + 1,
+                columns.end
+            ),
+            WorksheetError::UnknownOperator { columns, token } => write!(
+                f,
+                "columns {}-{}: unknown operator '{token}'",
+                columns.start + 1,
+                columns.end
+            ),
+            WorksheetError::OperandTooLarge { columns, digits } => write!(
+                f,
+                "columns {}-{}: operand {digits} is too large",
+                columns.start + 1,
+                columns.end
+            ),
+            WorksheetError::MultipleOperators { columns, tokens } => write!(
+                f,
+                "columns {}-{}: more than one operator ({})",
+                columns.start + 1,
+                columns.end,
+                tokens.join(", ")
+            ),
+            WorksheetError::Arithmetic { columns, error } => {
+                write!(f, "columns {}-{}: {error}", columns.start + 1, columns.end)
+            }
+            WorksheetError::TotalOverflow => write!(f, "grand total overflows i128"),
+        }
+    }
+}
+
+impl std::error::Error for WorksheetError {}
+
+/// One problem of the worksheet: the operand rows cut to the problem's
+/// columns (digits and spaces only, all the same width) and its operator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub operands: Vec<Vec<u8>>,
+    pub operator: Operator,
+    /// Half-open, 0-based column span.
+    pub columns: Range<usize>,
+}
+
+impl Problem {
+    /// The digit strings of each number under the given reading; rows or
+    /// columns without digits are skipped.
+    pub fn numbers(&self, reading: Reading) -> Vec<String> {
+        let digits = |cells: &mut dyn Iterator<Item = u8>| -> String {
+            cells.filter(u8::is_ascii_digit).map(char::from).collect()
+        };
+        let numbers: Vec<String> = match reading {
+            Reading::Rows => self
+                .operands
+                .iter()
+                .map(|row| digits(&mut row.iter().copied()))
+                .collect(),
+            Reading::Columns => (0..self.columns.len())
+                .rev()
+                .map(|col| digits(&mut self.operands.iter().map(|row| row[col])))
+                .collect(),
+        };
+        numbers.into_iter().filter(|n| !n.is_empty()).collect()
+    }
+
+    pub fn solve(&self, reading: Reading) -> Result<i128, WorksheetError> {
+        let operands = self
+            .numbers(reading)
+            .into_iter()
+            .map(|digits| {
+                digits.parse().map_err(|_| WorksheetError::OperandTooLarge {
+                    columns: self.columns.clone(),
+                    digits,
+                })
+            })
+            .collect::<Result<Vec<i128>, _>>()?;
+        self.operator
+            .apply(&operands)
+            .map_err(|error| WorksheetError::Arithmetic {
+                columns: self.columns.clone(),
+                error,
+            })
+    }
+}
+
+/// The lines of the worksheet without trailing blank or whitespace-only
+/// lines, which would otherwise be taken for the operator row.
+pub fn worksheet_lines(input: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = input.lines().collect();
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Splits the worksheet into problems. Every line is padded with spaces to
+/// the widest line; the last line holds the operators and the lines above it
+/// the operands.
+pub fn parse_worksheet(input: &str) -> Result<Vec<Problem>, WorksheetError> {
+    let lines = worksheet_lines(input);
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(idx) = line.find('\t') {
+            return Err(WorksheetError::Tab {
+                line: i + 1,
+                col: line[..idx].chars().count() + 1,
+            });
+        }
+    }
+    let Some((operator_row, operand_rows)) = lines.split_last() else {
+        return Err(WorksheetError::Empty);
+    };
+    if operator_row
+        .bytes()
+        .all(|b| b == b' ' || b.is_ascii_digit())
+    {
+        return Err(WorksheetError::MissingOperatorRow { line: lines.len() });
+    }
+    if let Some((idx, ch)) = operator_row
+        .char_indices()
+        .find(|&(_, ch)| ch != ' ' && !ch.is_ascii_graphic())
+    {
+        return Err(WorksheetError::UnexpectedChar {
+            line: lines.len(),
+            col: operator_row[..idx].chars().count() + 1,
+            ch,
+        });
+    }
+
+    let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    let mut rows: Vec<Vec<u8>> = Vec::with_capacity(operand_rows.len());
+    for (i, line) in operand_rows.iter().enumerate() {
+        if let Some((idx, ch)) = line
+            .char_indices()
+            .find(|&(_, ch)| ch != ' ' && !ch.is_ascii_digit())
+        {
+            return Err(WorksheetError::UnexpectedChar {
+                line: i + 1,
+                col: line[..idx].chars().count() + 1,
+                ch,
+            });
+        }
+        let mut row = line.as_bytes().to_vec();
+        row.resize(width, b' ');
+        rows.push(row);
+    }
+    let mut operator_row = operator_row.as_bytes().to_vec();
+    operator_row.resize(width, b' ');
+
+    let mut all_rows = rows.clone();
+    all_rows.push(operator_row.clone());
+    find_problems(&all_rows)
+        .into_iter()
+        .map(|columns| {
+            let token = String::from_utf8_lossy(&operator_row[columns.clone()])
+                .trim()
+                .to_string();
+            let tokens: Vec<&str> = token.split_whitespace().collect();
+            match tokens.len() {
+                0 => return Err(WorksheetError::MissingOperator { columns }),
+                1 => {}
+                _ => {
+                    return Err(WorksheetError::MultipleOperators {
+                        columns,
+                        tokens: tokens.iter().map(|t| t.to_string()).collect(),
+                    })
+                }
+            }
+            let operator = token.parse().map_err(|_| WorksheetError::UnknownOperator {
+                columns: columns.clone(),
+                token,
+            })?;
+            Ok(Problem {
+                operands: rows
+                    .iter()
+                    .map(|row| row[columns.clone()].to_vec())
+                    .collect(),
+                operator,
+                columns,
+            })
+        })
+        .collect()
+}
+
+/// Column spans of the problems: maximal runs of columns that are not
+/// blank in every row. All rows must have the same width.
+pub fn find_problems(rows: &[Vec<u8>]) -> Vec<Range<usize>> {
+    let width = rows.first().map_or(0, Vec::len);
+    let mut blank = vec![true; width];
+    for row in rows {
+        for (col, &b) in row.iter().enumerate() {
+            if b != b' ' {
+                blank[col] = false;
+            }
+        }
+    }
+
+    let mut problems = Vec::new();
+    let mut start = None;
+    for (col, &is_blank) in blank.iter().enumerate() {
+        match (is_blank, start) {
+            (true, Some(s)) => {
+                problems.push(s..col);
+                start = None;
+            }
+            (false, None) => start = Some(col),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        problems.push(s..width);
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        find_problems, parse_worksheet, ArithmeticError, Operator, Reading, WorksheetError,
+    };
+
+    const EXAMPLE: &str = "\
+123 328  51 64 
+ 45 64  387 23 
+  6 98  215 314
+*   +   *   +  
+";
+
+    #[test]
+    fn test_find_problems() {
+        let rows: Vec<Vec<u8>> = EXAMPLE.lines().map(|l| l.as_bytes().to_vec()).collect();
+        assert_eq!(find_problems(&rows), vec![0..3, 4..7, 8..11, 12..15]);
+        assert!(find_problems(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_example() {
+        let problems = parse_worksheet(EXAMPLE).unwrap();
+        let operators: Vec<Operator> = problems.iter().map(|p| p.operator).collect();
+        assert_eq!(
+            operators,
+            vec![
+                Operator::Multiply,
+                Operator::Add,
+                Operator::Multiply,
+                Operator::Add
+            ]
+        );
+        assert_eq!(problems[2].operands, vec![b" 51", b"387", b"215"]);
+        assert_eq!(problems[3].numbers(Reading::Rows), ["64", "23", "314"]);
+        assert_eq!(problems[3].numbers(Reading::Columns), ["4", "431", "623"]);
+    }
+
+    #[test]
+    fn test_ragged_lines_are_padded() {
+        let problems = parse_worksheet("12 3\n4\n+  *").unwrap();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].solve(Reading::Rows), Ok(16));
+        assert_eq!(problems[1].solve(Reading::Rows), Ok(3));
+        assert_eq!(problems[0].solve(Reading::Columns), Ok(2 + 14));
+    }
+
+    #[test]
+    fn test_trailing_blank_lines_are_ignored() {
+        let problems = parse_worksheet("1 2\n3 4\n+ *\n   \n\t\n\n").unwrap();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[1].solve(Reading::Rows), Ok(8));
+    }
+
+    #[test]
+    fn test_rejects_malformed_layouts() {
+        assert_eq!(parse_worksheet(""), Err(WorksheetError::Empty));
+        assert_eq!(parse_worksheet(" \n\n"), Err(WorksheetError::Empty));
+        assert_eq!(
+            parse_worksheet("1 2\n3\t4\n+ *\n"),
+            Err(WorksheetError::Tab { line: 2, col: 2 })
+        );
+        assert_eq!(
+            parse_worksheet("1 2\n3 4\n"),
+            Err(WorksheetError::MissingOperatorRow { line: 2 })
+        );
+        assert_eq!(
+            parse_worksheet("123\n456\n+*\n"),
+            Err(WorksheetError::UnknownOperator {
+                columns: 0..3,
+                token: "+*".to_string()
+            })
+        );
+        assert_eq!(
+            parse_worksheet("123\n456\n+ *\n"),
+            Err(WorksheetError::MultipleOperators {
+                columns: 0..3,
+                tokens: vec!["+".to_string(), "*".to_string()]
+            })
+        );
+        assert_eq!(
+            parse_worksheet("1 2\n3 4\n+  \n"),
+            Err(WorksheetError::MissingOperator { columns: 2..3 })
+        );
+        assert_eq!(
+            parse_worksheet("1 2\n3 x\n+ *\n"),
+            Err(WorksheetError::UnexpectedChar {
+                line: 2,
+                col: 3,
+                ch: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn test_operators_fold_left_to_right() {
+        let apply = |op: &str, operands: &[i128]| op.parse::<Operator>().unwrap().apply(operands);
+        assert_eq!(apply("-", &[20, 5, 3]), Ok(12));
+        assert_eq!(apply("/", &[100, 5, 3]), Ok(6));
+        assert_eq!(apply("^", &[2, 3, 2]), Ok(64));
+        assert_eq!(apply("min", &[7, 2, 9]), Ok(2));
+        assert_eq!(apply("max", &[7, 2, 9]), Ok(9));
+        assert_eq!(apply("+", &[]), Err(ArithmeticError::NoOperands));
+        assert!("%".parse::<Operator>().is_err());
+    }
+
+    #[test]
+    fn test_arithmetic_errors() {
+        let apply = |op: &str, operands: &[i128]| op.parse::<Operator>().unwrap().apply(operands);
+        assert_eq!(apply("/", &[4, 0]), Err(ArithmeticError::DivisionByZero));
+        assert_eq!(apply("*", &[i128::MAX, 2]), Err(ArithmeticError::Overflow));
+        assert_eq!(apply("^", &[10, 39]), Err(ArithmeticError::Overflow));
+        assert_eq!(apply("^", &[1, 1 << 40]), Ok(1));
+        assert_eq!(apply("-", &[0, 5]), Ok(-5));
+
+        let problems = parse_worksheet("99\n99\n99\n^ \n").unwrap();
+        assert_eq!(
+            problems[0].solve(Reading::Rows),
+            Err(WorksheetError::Arithmetic {
+                columns: 0..2,
+                error: ArithmeticError::Overflow
+            })
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use crate::count::{Count, Tally};
+use crate::manifold::{Cell, Direction, Manifold};
+use common::grid::{Grid, Pos};
+use std::fmt;
+
+/// A beam occupying `pos` and about to move one cell in `dir`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Beam {
+    pub pos: Pos,
+    pub dir: Direction,
+}
+
+/// What happens when a beam moves one cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// The cell the beam moved into, or `None` if it left the grid.
+    pub entered: Option<Pos>,
+    /// Whether that cell split the beam.
+    pub split: bool,
+    /// Number of timelines that end here, by leaving the grid or being
+    /// absorbed. A `^` at the edge of the grid ends the branch that would
+    /// leave it.
+    pub ends: usize,
+    /// Beams that continue.
+    pub beams: [Option<Beam>; 2],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BeamError {
+    Cycle { pos: Pos, dir: Direction },
+}
+
+impl fmt::Display for BeamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BeamError::Cycle { pos, dir } => write!(
+                f,
+                "a beam at {pos} heading {dir:?} is in a loop it can leave, so the timelines are unbounded"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BeamError {}
+
+impl Manifold {
+    pub fn start_beam(&self) -> Beam {
+        Beam {
+            pos: self.start,
+            dir: Direction::Down,
+        }
+    }
+
+    pub fn advance(&self, beam: Beam) -> Step {
+        let (d_row, d_col) = beam.dir.delta();
+        let Some(pos) = self.cells.offset(beam.pos, d_row, d_col) else {
+            return Step {
+                entered: None,
+                split: false,
+                ends: 1,
+                beams: [None, None],
+            };
+        };
+        let cell = self.cells[pos];
+        let split = cell.is_splitter() && beam.dir.is_vertical();
+        let to = |pos: Option<Pos>, dir: Direction| pos.map(|pos| Beam { pos, dir });
+        let beams = match cell {
+            Cell::Absorber => [None, None],
+            Cell::Splitter if split => [
+                to(self.cells.offset(pos, 0, -1), beam.dir),
+                to(self.cells.offset(pos, 0, 1), beam.dir),
+            ],
+            Cell::SideSplitter if split => [
+                to(Some(pos), Direction::Left),
+                to(Some(pos), Direction::Right),
+            ],
+            _ => [to(Some(pos), cell.reflect(beam.dir)), None],
+        };
+        let ends = match cell {
+            Cell::Absorber => 1,
+            Cell::Splitter if split => beams.iter().filter(|beam| beam.is_none()).count(),
+            _ => 0,
+        };
+        Step {
+            entered: Some(pos),
+            split,
+            ends,
+            beams,
+        }
+    }
+
+    fn state_index(&self, beam: Beam) -> usize {
+        (beam.pos.row * self.cells.width() + beam.pos.col) * 4 + beam.dir.index()
+    }
+}
+
+/// Every cell a beam passes through and every splitter that split one.
+pub struct Trace {
+    pub lit: Grid<bool>,
+    pub hit: Grid<bool>,
+}
+
+impl Trace {
+    pub fn split_count(&self) -> usize {
+        self.hit.count(&true)
+    }
+}
+
+/// Follows all beams from the start. Each (cell, direction) state is
+/// expanded at most once, which also stops beams caught in a loop.
+pub fn trace(manifold: &Manifold) -> Trace {
+    let (width, height) = (manifold.cells.width(), manifold.cells.height());
+    let mut lit = Grid::new(width, height, false);
+    let mut hit = Grid::new(width, height, false);
+    let mut seen = vec![false; width * height * 4];
+
+    let start = manifold.start_beam();
+    lit[start.pos] = true;
+    seen[manifold.state_index(start)] = true;
+    let mut queue = vec![start];
+    while let Some(beam) = queue.pop() {
+        let step = manifold.advance(beam);
+        if let Some(pos) = step.entered {
+            lit[pos] = true;
+            hit[pos] |= step.split;
+        }
+        for next in step.beams.into_iter().flatten() {
+            lit[next.pos] = true;
+            let seen = &mut seen[manifold.state_index(next)];
+            if !*seen {
+                *seen = true;
+                queue.push(next);
+            }
+        }
+    }
+
+    Trace { lit, hit }
+}
+
+/// Beam states reachable from the start that can still lead to a timeline
+/// ending, found by walking the reachable states backwards from the ones
+/// where a timeline ends.
+fn can_finish(manifold: &Manifold) -> Vec<bool> {
+    let states = manifold.cells.width() * manifold.cells.height() * 4;
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); states];
+    let mut seen = vec![false; states];
+    let mut finish = vec![false; states];
+    let mut ready = Vec::new();
+
+    let start = manifold.start_beam();
+    seen[manifold.state_index(start)] = true;
+    let mut stack = vec![start];
+    while let Some(beam) = stack.pop() {
+        let i = manifold.state_index(beam);
+        let step = manifold.advance(beam);
+        if step.ends > 0 {
+            finish[i] = true;
+            ready.push(i);
+        }
+        for next in step.beams.into_iter().flatten() {
+            let j = manifold.state_index(next);
+            predecessors[j].push(i);
+            if !seen[j] {
+                seen[j] = true;
+                stack.push(next);
+            }
+        }
+    }
+
+    while let Some(j) = ready.pop() {
+        for &i in &predecessors[j] {
+            if !finish[i] {
+                finish[i] = true;
+                ready.push(i);
+            }
+        }
+    }
+    finish
+}
+
+/// Number of distinct paths from the start to a point where the beam leaves
+/// the grid or is absorbed. Beams caught in a loop that no path leads out of
+/// never finish and add nothing. A loop that beams can also leave allows
+/// unboundedly many timelines, so it is reported as an error.
+pub fn count_timelines<T: Tally>(manifold: &Manifold, one: T) -> Result<T, BeamError> {
+    const NEW: u8 = 0;
+    const OPEN: u8 = 1;
+    const DONE: u8 = 2;
+
+    let states = manifold.cells.width() * manifold.cells.height() * 4;
+    let finish = can_finish(manifold);
+    let mut color = vec![NEW; states];
+    let zero = one.zero();
+    let mut counts = vec![zero.clone(); states];
+    let cycle = |beam: Beam| BeamError::Cycle {
+        pos: beam.pos,
+        dir: beam.dir,
+    };
+
+    let start = manifold.start_beam();
+    if !finish[manifold.state_index(start)] {
+        return Ok(zero);
+    }
+    let mut stack = vec![(start, false)];
+    while let Some((beam, expanded)) = stack.pop() {
+        let i = manifold.state_index(beam);
+        let step = manifold.advance(beam);
+        let beams = step.beams.into_iter().flatten();
+        let beams = beams.filter(|&next| finish[manifold.state_index(next)]);
+        if expanded {
+            let mut total = zero.clone();
+            for _ in 0..step.ends {
+                total.add(&one);
+            }
+            for next in beams {
+                total.add(&counts[manifold.state_index(next)]);
+            }
+            counts[i] = total;
+            color[i] = DONE;
+            continue;
+        }
+        match color[i] {
+            DONE => continue,
+            OPEN => return Err(cycle(beam)),
+            _ => {}
+        }
+        color[i] = OPEN;
+        stack.push((beam, true));
+        for next in beams {
+            match color[manifold.state_index(next)] {
+                OPEN => return Err(cycle(next)),
+                NEW => stack.push((next, false)),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(counts.swap_remove(manifold.state_index(start)))
+}
+
+/// Number of timelines that reach each splitter and are split there. States
+/// are visited in topological order, so a splitter that is only reached
+/// through a loop, where the number is unbounded, stays `None` like the
+/// splitters that never split a beam.
+pub fn splitter_timelines(manifold: &Manifold) -> Grid<Option<Count>> {
+    let (width, height) = (manifold.cells.width(), manifold.cells.height());
+    let states = width * height * 4;
+
+    let start = manifold.start_beam();
+    let mut indegree = vec![0usize; states];
+    let mut seen = vec![false; states];
+    seen[manifold.state_index(start)] = true;
+    let mut stack = vec![start];
+    while let Some(beam) = stack.pop() {
+        for next in manifold.advance(beam).beams.into_iter().flatten() {
+            let j = manifold.state_index(next);
+            indegree[j] += 1;
+            if !seen[j] {
+                seen[j] = true;
+                stack.push(next);
+            }
+        }
+    }
+
+    let mut timelines = Grid::new(width, height, None);
+    let mut arriving = vec![Count::Small(0); states];
+    let mut ready = Vec::new();
+    if indegree[manifold.state_index(start)] == 0 {
+        arriving[manifold.state_index(start)] = Count::one();
+        ready.push(start);
+    }
+    while let Some(beam) = ready.pop() {
+        let count = std::mem::replace(&mut arriving[manifold.state_index(beam)], Count::Small(0));
+        let step = manifold.advance(beam);
+        if let (true, Some(pos)) = (step.split, step.entered) {
+            timelines[pos]
+                .get_or_insert_with(|| count.zero())
+                .add(&count);
+        }
+        for next in step.beams.into_iter().flatten() {
+            let j = manifold.state_index(next);
+            arriving[j].add(&count);
+            indegree[j] -= 1;
+            if indegree[j] == 0 {
+                ready.push(next);
+            }
+        }
+    }
+
+    timelines
+}
@@ -0,0 +1,80 @@
+use crate::count::Count;
+use crate::manifold::Manifold;
+use crate::{reference, sweep};
+use common::rng::{XorShift, DEFAULT_SEED};
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+/// A `width` x `height` downward-only manifold with `S` in the middle of the
+/// top row and pseudo-random splitters on every other row.
+pub fn generate(width: usize, height: usize, seed: u64) -> Manifold {
+    let mut rng = XorShift::new(seed);
+    let mut text = String::with_capacity((width + 1) * height);
+    for row in 0..height {
+        for col in 0..width {
+            let roll = rng.below(3);
+            let ch = if row == 0 && col == width / 2 {
+                'S'
+            } else if row % 2 == 0 && row > 0 && roll == 0 {
+                '^'
+            } else {
+                '.'
+            };
+            text.push(ch);
+        }
+        text.push('\n');
+    }
+    Manifold::parse(&text).expect("generated manifold is valid")
+}
+
+/// Best of `rounds` runs of `f`, with its last result.
+fn time<R>(rounds: usize, mut f: impl FnMut() -> R) -> (Duration, R) {
+    let mut best = Duration::MAX;
+    let mut result = None;
+    for _ in 0..rounds {
+        let started = Instant::now();
+        result = Some(f());
+        best = best.min(started.elapsed());
+    }
+    (best, result.unwrap())
+}
+
+/// Times the dense sweep against the `HashMap` reference on a generated
+/// manifold and checks that both give the same answers.
+pub fn run(width: usize, height: usize, rounds: usize) -> String {
+    let manifold = generate(width, height, DEFAULT_SEED);
+    let cells = (width * height) as f64;
+    let mut out = String::new();
+    writeln!(out, "manifold {width}x{height}, best of {rounds}").unwrap();
+
+    let (old, expected) = time(rounds, || reference::split_count(&manifold));
+    let (new, found) = time(rounds, || sweep::split_count(&manifold));
+    assert_eq!(found, expected, "part 1 differs from the reference");
+    report(&mut out, "part1", old, new, cells);
+
+    let (old, expected) = time(rounds, || {
+        reference::count_timelines(&manifold, Count::one())
+    });
+    let (new, found) = time(rounds, || sweep::count_timelines(&manifold, Count::one()));
+    assert_eq!(found, expected, "part 2 differs from the reference");
+    report(&mut out, "part2", old, new, cells);
+    writeln!(
+        out,
+        "answers: {} splits, {found} timelines",
+        sweep::split_count(&manifold)
+    )
+    .unwrap();
+    out
+}
+
+fn report(out: &mut String, part: &str, old: Duration, new: Duration, cells: f64) {
+    let rate = |d: Duration| cells / d.as_secs_f64() / 1e6;
+    writeln!(
+        out,
+        "{part}: reference {old:>10.2?} ({:>8.1} Mcells/s)  dense {new:>10.2?} ({:>8.1} Mcells/s)  {:.1}x",
+        rate(old),
+        rate(new),
+        old.as_secs_f64() / new.as_secs_f64()
+    )
+    .unwrap();
+}
@@ -0,0 +1,167 @@
+use common::bignum::BigUint;
+use std::fmt;
+
+/// A timeline count in some arithmetic. The counting code only ever adds
+/// counts together, starting from the count of a single timeline.
+pub trait Tally: Clone {
+    /// Zero in the same arithmetic as `self` (for modular counts, the same
+    /// modulus).
+    fn zero(&self) -> Self;
+
+    fn is_zero(&self) -> bool;
+
+    fn add(&mut self, other: &Self);
+}
+
+/// Exact count that stays a `u128` until an addition would overflow it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Count {
+    Small(u128),
+    Big(BigUint),
+}
+
+impl Count {
+    pub fn one() -> Self {
+        Count::Small(1)
+    }
+
+    /// Number of significant bits.
+    pub fn bits(&self) -> u64 {
+        match self {
+            Count::Small(n) => (u128::BITS - n.leading_zeros()) as u64,
+            Count::Big(n) => n.bits(),
+        }
+    }
+}
+
+impl Tally for Count {
+    fn zero(&self) -> Self {
+        Count::Small(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Count::Small(0)
+    }
+
+    fn add(&mut self, other: &Self) {
+        match (&mut *self, other) {
+            (Count::Small(a), Count::Small(b)) => match a.checked_add(*b) {
+                Some(sum) => *a = sum,
+                None => {
+                    let mut sum = BigUint::from(*a);
+                    sum += *b;
+                    *self = Count::Big(sum);
+                }
+            },
+            (Count::Small(a), Count::Big(b)) => {
+                let mut sum = b.clone();
+                sum += *a;
+                *self = Count::Big(sum);
+            }
+            (Count::Big(a), Count::Small(b)) => *a += *b,
+            (Count::Big(a), Count::Big(b)) => *a += b,
+        }
+    }
+}
+
+impl PartialEq<u128> for Count {
+    fn eq(&self, other: &u128) -> bool {
+        match self {
+            Count::Small(n) => n == other,
+            Count::Big(n) => n == other,
+        }
+    }
+}
+
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Count::Small(n) => n.fmt(f),
+            Count::Big(n) => n.fmt(f),
+        }
+    }
+}
+
+/// Count modulo a prime that fits in a `u64`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Modular {
+    value: u64,
+    modulus: u64,
+}
+
+impl Modular {
+    pub fn one(modulus: u64) -> Result<Self, String> {
+        if !is_prime(modulus) {
+            return Err(format!("modulus {modulus} is not a prime"));
+        }
+        Ok(Modular {
+            value: 1 % modulus,
+            modulus,
+        })
+    }
+}
+
+impl Tally for Modular {
+    fn zero(&self) -> Self {
+        Modular {
+            value: 0,
+            modulus: self.modulus,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.value = ((self.value as u128 + other.value as u128) % self.modulus as u128) as u64;
+    }
+}
+
+impl fmt::Display for Modular {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// Deterministic Miller-Rabin; these bases are enough for every `u64`.
+pub fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    for p in BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    let mul = |a: u64, b: u64| ((a as u128 * b as u128) % n as u128) as u64;
+    let pow = |mut base: u64, mut exp: u64| {
+        let mut result = 1;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = mul(result, base);
+            }
+            base = mul(base, base);
+            exp >>= 1;
+        }
+        result
+    };
+
+    let zeros = (n - 1).trailing_zeros();
+    let odd = (n - 1) >> zeros;
+    BASES.iter().all(|&a| {
+        let mut x = pow(a, odd);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..zeros {
+            x = mul(x, x);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
@@ -1,138 +1,270 @@
+mod beam;
+mod bench;
+mod count;
+mod manifold;
+mod reference;
+mod render;
+mod sweep;
+
+use beam::{count_timelines, trace};
+use count::{Count, Modular, Tally};
+use manifold::{Manifold, ManifoldError};
+use render::{render_image, render_text, splitter_report, Usage};
+use std::error::Error;
 use std::io::{self, Read};
-use std::collections::HashSet;
-
-fn solve_part1(input: &str) -> usize {
-    let grid: Vec<Vec<char>> = input
-        .lines()
-        .map(|line| line.chars().collect())
-        .collect();
-    
-    if grid.is_empty() {
-        return 0;
-    }
-    
-    let rows = grid.len();
-    let cols = grid[0].len();
-    
-    let mut start_pos = None;
-    for (r, row) in grid.iter().enumerate() {
-        for (c, &ch) in row.iter().enumerate() {
-            if ch == 'S' {
-                start_pos = Some((r, c));
-                break;
-            }
-        }
-        if start_pos.is_some() {
-            break;
-        }
+use std::path::PathBuf;
+
+fn solve_part1(input: &str) -> Result<usize, ManifoldError> {
+    let manifold = Manifold::parse(input)?;
+    if manifold.is_downward_only() {
+        Ok(sweep::split_count(&manifold))
+    } else {
+        Ok(trace(&manifold).split_count())
     }
-    
-    let (start_r, start_c) = start_pos.expect("No S found");
-    
-    let mut active_beams = HashSet::new();
-    active_beams.insert((start_r, start_c));
-    
-    let mut split_count = 0;
-    
-    for row in (start_r + 1)..rows {
-        let mut next_beams = HashSet::new();
-        
-        for &(_r, c) in &active_beams {
-            if grid[row][c] == '^' {
-                split_count += 1;
-                
-                if c > 0 {
-                    next_beams.insert((row, c - 1));
-                }
-                
-                if c + 1 < cols {
-                    next_beams.insert((row, c + 1));
-                }
-            } else {
-                next_beams.insert((row, c));
-            }
-        }
-        
-        active_beams = next_beams;
+}
+
+fn solve_part2<T: Tally>(input: &str, one: T) -> Result<T, Box<dyn Error>> {
+    let manifold = Manifold::parse(input)?;
+    if manifold.is_downward_only() {
+        Ok(sweep::count_timelines(&manifold, one))
+    } else {
+        Ok(count_timelines(&manifold, one)?)
     }
-    
-    split_count
 }
 
-fn solve_part2(input: &str) -> usize {
-    let grid: Vec<Vec<char>> = input
-        .lines()
-        .map(|line| line.chars().collect())
-        .collect();
-    
-    if grid.is_empty() {
-        return 0;
-    }
-    
-    let rows = grid.len();
-    let cols = grid[0].len();
-    
-    let mut start_pos = None;
-    for (r, row) in grid.iter().enumerate() {
-        for (c, &ch) in row.iter().enumerate() {
-            if ch == 'S' {
-                start_pos = Some((r, c));
-                break;
+fn fail(err: impl std::fmt::Display) -> ! {
+    eprintln!("{err}");
+    std::process::exit(1);
+}
+
+struct Options {
+    modulus: Option<u64>,
+    color: bool,
+    out: Option<PathBuf>,
+    scale: usize,
+    width: usize,
+    height: usize,
+    rounds: usize,
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: Option<&String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{flag} needs a value"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid {flag} value '{value}'"))
+}
+
+fn parse_options(command: &str, flags: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        modulus: None,
+        color: false,
+        out: None,
+        scale: 4,
+        width: 4096,
+        height: 2048,
+        rounds: 3,
+    };
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match (command, flag.as_str()) {
+            ("part2", "--modulo") => options.modulus = Some(parse_number(flag, flags.next())?),
+            ("render", "--color") => options.color = true,
+            ("render", "--out") => {
+                let path = flags.next().ok_or("--out needs a file name")?;
+                options.out = Some(PathBuf::from(path));
             }
-        }
-        if start_pos.is_some() {
-            break;
+            ("render", "--scale") => options.scale = parse_number(flag, flags.next())?,
+            ("bench", "--width") => options.width = parse_number(flag, flags.next())?,
+            ("bench", "--height") => options.height = parse_number(flag, flags.next())?,
+            ("bench", "--rounds") => options.rounds = parse_number(flag, flags.next())?,
+            _ => return Err(format!("unknown flag '{flag}' for {command}")),
         }
     }
-    
-    let (start_r, start_c) = start_pos.expect("No S found");
-    
-    let mut position_counts: std::collections::HashMap<(usize, usize), usize> = std::collections::HashMap::new();
-    position_counts.insert((start_r, start_c), 1);
-    
-    for row in (start_r + 1)..rows {
-        let mut next_counts: std::collections::HashMap<(usize, usize), usize> = std::collections::HashMap::new();
-        
-        for (&(_r, c), &count) in &position_counts {
-            let cell = grid[row][c];
-            if cell == '^' {
-                if c > 0 {
-                    *next_counts.entry((row, c - 1)).or_insert(0) += count;
-                }
-                if c + 1 < cols {
-                    *next_counts.entry((row, c + 1)).or_insert(0) += count;
-                }
-            } else if cell == '.' || cell == 'S' {
-                *next_counts.entry((row, c)).or_insert(0) += count;
-            }
-        }
-        
-        position_counts = next_counts;
+    if options.width == 0 || options.height == 0 || options.rounds == 0 {
+        return Err("bench needs a positive width, height and round count".to_string());
     }
-    
-    position_counts.values().sum()
+    if options.scale == 0 {
+        return Err("scale must be at least 1".to_string());
+    }
+    Ok(options)
+}
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {program} <part1|part2|render|splitters|bench>");
+    eprintln!("       part2: [--modulo P] reports the timeline count modulo the prime P");
+    eprintln!("       render: [--color] [--out FILE.ppm|FILE.png] [--scale N] draws the lit cells and splitter usage");
+    eprintln!(
+        "       splitters: lists the timelines split by each splitter and the splitters never hit"
+    );
+    eprintln!("       bench: [--width W] [--height H] [--rounds N] times the sweep on a generated manifold");
+    eprintln!("Example: {program} part1 < input.txt");
+    std::process::exit(1);
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 || (args[1] != "part1" && args[1] != "part2") {
-        eprintln!("Usage: {} <part1|part2>", args[0]);
-        eprintln!("Example: {} part1 < input.txt", args[0]);
-        std::process::exit(1);
+    if args.len() < 2
+        || !["part1", "part2", "render", "splitters", "bench"].contains(&args[1].as_str())
+    {
+        usage(&args[0]);
+    }
+
+    let command = args[1].as_str();
+    let options = parse_options(command, &args[2..]).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        usage(&args[0]);
+    });
+
+    if command == "bench" {
+        print!(
+            "{}",
+            bench::run(options.width, options.height, options.rounds)
+        );
+        return;
     }
-    
-    let part = &args[1];
 
     let mut input = String::new();
     io::stdin()
         .read_to_string(&mut input)
         .expect("failed to read input");
 
-    let answer = if part == "part1" {
+    if command == "render" || command == "splitters" {
+        let manifold = Manifold::parse(&input).unwrap_or_else(|err| fail(err));
+        let usage = Usage::new(&manifold);
+        match (command, &options.out) {
+            ("splitters", _) => print!("{}", splitter_report(&manifold, &usage)),
+            (_, Some(path)) => {
+                render_image(&manifold, &usage, options.scale)
+                    .save(path)
+                    .expect("failed to write image");
+                println!("wrote {}", path.display());
+            }
+            (_, None) => print!("{}", render_text(&manifold, &usage, options.color)),
+        }
+        return;
+    }
+
+    let answer = if command == "part1" {
         solve_part1(&input)
+            .map(|splits| splits.to_string())
+            .map_err(Box::from)
+    } else if let Some(modulus) = options.modulus {
+        let one = Modular::one(modulus).unwrap_or_else(|err| fail(err));
+        solve_part2(&input, one).map(|count| count.to_string())
     } else {
-        solve_part2(&input)
+        solve_part2(&input, Count::one()).map(|count| count.to_string())
     };
-    
-    println!("{answer}");
+
+    match answer {
+        Ok(answer) => println!("{answer}"),
+        Err(err) => fail(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve_part1, solve_part2};
+    use crate::count::{Count, Modular};
+
+    const EXAMPLE: &str = "\
+.......S.......
+...............
+.......^.......
+...............
+......^.^......
+...............
+.....^.^.^.....
+...............
+....^.^...^....
+...............
+...^.^...^.^...
+...............
+..^...^.....^..
+...............
+.^.^.^.^.^...^.
+...............
+";
+
+    /// Adds a row of absorbers under `input`, which sends it through the
+    /// general simulator without changing either answer.
+    fn absorbed(input: &str) -> String {
+        let width = input.lines().next().unwrap().len();
+        format!("{input}{}\n", "#".repeat(width))
+    }
+
+    #[test]
+    fn test_example_part1() {
+        assert_eq!(solve_part1(EXAMPLE).unwrap(), 21);
+        assert_eq!(solve_part1(&absorbed(EXAMPLE)).unwrap(), 21);
+    }
+
+    #[test]
+    fn test_example_part2() {
+        assert_eq!(solve_part2(EXAMPLE, Count::one()).unwrap(), 40);
+        assert_eq!(solve_part2(&absorbed(EXAMPLE), Count::one()).unwrap(), 40);
+        let one = Modular::one(7).unwrap();
+        assert_eq!(solve_part2(EXAMPLE, one).unwrap().to_string(), "5");
+    }
+
+    #[test]
+    fn test_splitters_at_the_edges() {
+        // The branch that would leave the grid ends its timeline there; the
+        // other one carries on down its own column.
+        let left = "S..\n...\n^..\n...\n";
+        let right = "..S\n...\n..^\n...\n";
+        let both = "S\n.\n^\n.\n";
+        let stacked = ".S\n..\n.^\n..\n^.\n..\n";
+        for (input, splits, timelines) in
+            [(left, 1, 2), (right, 1, 2), (both, 1, 2), (stacked, 2, 3)]
+        {
+            for input in [input.to_string(), absorbed(input)] {
+                assert_eq!(solve_part1(&input).unwrap(), splits, "{input}");
+                assert_eq!(
+                    solve_part2(&input, Count::one()).unwrap(),
+                    timelines,
+                    "{input}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_manifolds_are_errors() {
+        assert!(solve_part1("..S..\n...\n").is_err());
+        assert!(solve_part1(".....\n").is_err());
+        assert!(solve_part2("S.S\n...\n", Count::one()).is_err());
+    }
+
+    #[test]
+    fn test_general_features() {
+        // A '-' sends the beam out both sides; '/' and '\' steer it back
+        // down into columns 0 and 4, where '#' absorbs one timeline.
+        let input = "\
+..S..
+.....
+/.-.\\
+.....
+....#
+";
+        assert_eq!(solve_part1(input).unwrap(), 1);
+        assert_eq!(solve_part2(input, Count::one()).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_closed_loop_has_no_timelines() {
+        // Both halves of the '-' split circle the mirrors forever without
+        // reaching an exit.
+        let input = "..S..\n./-\\.\n.....\n.\\./.\n";
+        assert_eq!(solve_part1(input).unwrap(), 1);
+        assert_eq!(solve_part2(input, Count::one()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_leaking_loop_makes_part2_an_error() {
+        // The right half of the split circles back through the '-', and on
+        // every lap the '^' sends one branch down and out of the grid.
+        let input = "..S...\n./-.\\.\n....^.\n.\\.../\n";
+        assert_eq!(solve_part1(input).unwrap(), 2);
+        assert!(solve_part2(input, Count::one()).is_err());
+    }
 }
@@ -0,0 +1,193 @@
+use common::grid::{Grid, GridError, Pos};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Direction::Up | Direction::Down)
+    }
+}
+
+/// What a beam does when it enters a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cell {
+    /// `.`: passes straight through.
+    Empty,
+    /// `S`: where the beam starts, heading down; otherwise empty.
+    Start,
+    /// `^`: a vertical beam continues in the same direction from the two
+    /// cells beside the splitter. Horizontal beams pass through.
+    Splitter,
+    /// `-`: a vertical beam leaves the splitter as two beams heading left
+    /// and right. Horizontal beams pass through.
+    SideSplitter,
+    /// `/`: turns right-moving beams up, down-moving beams left and so on.
+    Mirror,
+    /// `\`: turns right-moving beams down, up-moving beams left and so on.
+    BackMirror,
+    /// `#`: stops the beam.
+    Absorber,
+}
+
+impl Cell {
+    pub fn from_char(ch: char) -> Option<Cell> {
+        match ch {
+            '.' => Some(Cell::Empty),
+            'S' => Some(Cell::Start),
+            '^' => Some(Cell::Splitter),
+            '-' => Some(Cell::SideSplitter),
+            '/' => Some(Cell::Mirror),
+            '\\' => Some(Cell::BackMirror),
+            '#' => Some(Cell::Absorber),
+            _ => None,
+        }
+    }
+
+    pub fn is_splitter(self) -> bool {
+        matches!(self, Cell::Splitter | Cell::SideSplitter)
+    }
+
+    /// Direction of a beam that enters this mirror moving in `dir`.
+    pub fn reflect(self, dir: Direction) -> Direction {
+        use Direction::*;
+        match (self, dir) {
+            (Cell::Mirror, Right) | (Cell::BackMirror, Left) => Up,
+            (Cell::Mirror, Left) | (Cell::BackMirror, Right) => Down,
+            (Cell::Mirror, Up) | (Cell::BackMirror, Down) => Right,
+            (Cell::Mirror, Down) | (Cell::BackMirror, Up) => Left,
+            _ => dir,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ManifoldError {
+    Grid(GridError),
+    UnknownCell { pos: Pos, ch: char },
+    MissingStart,
+    MultipleStarts { first: Pos, second: Pos },
+}
+
+impl fmt::Display for ManifoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifoldError::Grid(err) => err.fmt(f),
+            ManifoldError::UnknownCell { pos, ch } => {
+                write!(f, "unknown cell {ch:?} at {pos}")
+            }
+            ManifoldError::MissingStart => write!(f, "the manifold has no start cell 'S'"),
+            ManifoldError::MultipleStarts { first, second } => {
+                write!(
+                    f,
+                    "the manifold has more than one 'S', at {first} and {second}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ManifoldError {}
+
+pub struct Manifold {
+    pub cells: Grid<Cell>,
+    pub start: Pos,
+}
+
+impl Manifold {
+    pub fn parse(input: &str) -> Result<Self, ManifoldError> {
+        let grid: Grid<char> = input.parse().map_err(ManifoldError::Grid)?;
+        if let Some((pos, &ch)) = grid.iter().find(|(_, &ch)| Cell::from_char(ch).is_none()) {
+            return Err(ManifoldError::UnknownCell { pos, ch });
+        }
+        let mut starts = grid.find_all(&'S');
+        let start = starts.next().ok_or(ManifoldError::MissingStart)?;
+        if let Some(second) = starts.next() {
+            return Err(ManifoldError::MultipleStarts {
+                first: start,
+                second,
+            });
+        }
+        let cells = grid.map(|&ch| Cell::from_char(ch).unwrap());
+        Ok(Manifold { cells, start })
+    }
+
+    /// Whether the manifold only has `.`, `S` and `^`, so every beam keeps
+    /// moving down and a row-by-row sweep gives the same answers as the
+    /// general simulator.
+    pub fn is_downward_only(&self) -> bool {
+        self.cells
+            .iter()
+            .all(|(_, &cell)| matches!(cell, Cell::Empty | Cell::Start | Cell::Splitter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cell, Direction, Manifold, ManifoldError};
+    use common::grid::{GridError, Pos};
+
+    #[test]
+    fn test_mirror_table() {
+        use Direction::*;
+        let turns = |cell: Cell| [Up, Down, Left, Right].map(|dir| cell.reflect(dir));
+        assert_eq!(turns(Cell::Mirror), [Right, Left, Down, Up]);
+        assert_eq!(turns(Cell::BackMirror), [Left, Right, Up, Down]);
+        assert_eq!(turns(Cell::Empty), [Up, Down, Left, Right]);
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(
+            Manifold::parse("..S..\n...\n").err(),
+            Some(ManifoldError::Grid(GridError::RaggedRow {
+                row: 1,
+                expected: 5,
+                found: 3
+            }))
+        );
+        assert_eq!(
+            Manifold::parse(".....\n..^..\n").err(),
+            Some(ManifoldError::MissingStart)
+        );
+        assert_eq!(Manifold::parse("").err(), Some(ManifoldError::MissingStart));
+        assert_eq!(
+            Manifold::parse("..S..\n.S...\n").err(),
+            Some(ManifoldError::MultipleStarts {
+                first: Pos::new(0, 2),
+                second: Pos::new(1, 1)
+            })
+        );
+        assert_eq!(
+            Manifold::parse("..S..\n..x..\n").err(),
+            Some(ManifoldError::UnknownCell {
+                pos: Pos::new(1, 2),
+                ch: 'x'
+            })
+        );
+        let manifold = Manifold::parse("\n..S..\n..^..\n\n").unwrap();
+        assert_eq!(manifold.start, Pos::new(0, 2));
+        assert!(manifold.is_downward_only());
+        let crlf = Manifold::parse("..S..\r\n..^..\r\n").unwrap();
+        assert_eq!(crlf.cells, manifold.cells);
+    }
+}
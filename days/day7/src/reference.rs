@@ -0,0 +1,95 @@
+//! The original downward sweep's `HashSet`/`HashMap` design, which rebuilds
+//! the beams every row. Kept as the oracle for `sweep` and the baseline for
+//! `bench`. Unlike the original, a branch that leaves the grid at the side
+//! ends its timeline instead of being dropped, so both sweeps share the
+//! corrected edge semantics.
+
+use crate::count::Tally;
+use crate::manifold::{Cell, Manifold};
+use common::grid::Pos;
+use std::collections::{HashMap, HashSet};
+
+pub fn split_count(manifold: &Manifold) -> usize {
+    let grid = &manifold.cells;
+    let rows = grid.height();
+    let cols = grid.width();
+    let start = manifold.start;
+
+    let mut active_beams = HashSet::new();
+    active_beams.insert((start.row, start.col));
+
+    let mut split_count = 0;
+
+    for row in (start.row + 1)..rows {
+        let mut next_beams = HashSet::new();
+
+        for &(_r, c) in &active_beams {
+            if grid[Pos::new(row, c)] == Cell::Splitter {
+                split_count += 1;
+
+                if c > 0 {
+                    next_beams.insert((row, c - 1));
+                }
+
+                if c + 1 < cols {
+                    next_beams.insert((row, c + 1));
+                }
+            } else {
+                next_beams.insert((row, c));
+            }
+        }
+
+        active_beams = next_beams;
+    }
+
+    split_count
+}
+
+pub fn count_timelines<T: Tally>(manifold: &Manifold, one: T) -> T {
+    let grid = &manifold.cells;
+    let rows = grid.height();
+    let cols = grid.width();
+    let start = manifold.start;
+    let zero = one.zero();
+
+    let mut position_counts: HashMap<(usize, usize), T> = HashMap::new();
+    position_counts.insert((start.row, start.col), one);
+    let mut ended = zero.clone();
+
+    for row in (start.row + 1)..rows {
+        let mut next_counts: HashMap<(usize, usize), T> = HashMap::new();
+
+        for (&(_r, c), count) in &position_counts {
+            if grid[Pos::new(row, c)] == Cell::Splitter {
+                if c > 0 {
+                    next_counts
+                        .entry((row, c - 1))
+                        .or_insert_with(|| zero.clone())
+                        .add(count);
+                } else {
+                    ended.add(count);
+                }
+                if c + 1 < cols {
+                    next_counts
+                        .entry((row, c + 1))
+                        .or_insert_with(|| zero.clone())
+                        .add(count);
+                } else {
+                    ended.add(count);
+                }
+            } else {
+                next_counts
+                    .entry((row, c))
+                    .or_insert_with(|| zero.clone())
+                    .add(count);
+            }
+        }
+
+        position_counts = next_counts;
+    }
+
+    position_counts.values().fold(ended, |mut total, count| {
+        total.add(count);
+        total
+    })
+}
@@ -0,0 +1,153 @@
+use crate::beam::{splitter_timelines, trace};
+use crate::count::Count;
+use crate::manifold::{Cell, Manifold};
+use common::grid::{Grid, Pos};
+use common::image::{Image, Rgb};
+use std::fmt::Write as _;
+
+const EMPTY: Rgb = [16, 16, 24];
+const LIT: Rgb = [250, 220, 90];
+const START: Rgb = [80, 220, 120];
+const WALL: Rgb = [200, 200, 210];
+const UNUSED: Rgb = [90, 90, 90];
+const LOOPING: Rgb = [220, 60, 220];
+const COLD: Rgb = [40, 90, 255];
+const HOT: Rgb = [255, 40, 40];
+
+/// Where the beams went and how many timelines each splitter split.
+pub struct Usage {
+    pub lit: Grid<bool>,
+    pub hit: Grid<bool>,
+    pub timelines: Grid<Option<Count>>,
+}
+
+impl Usage {
+    pub fn new(manifold: &Manifold) -> Self {
+        let trace = trace(manifold);
+        Usage {
+            lit: trace.lit,
+            hit: trace.hit,
+            timelines: splitter_timelines(manifold),
+        }
+    }
+
+    fn max_bits(&self) -> u64 {
+        self.timelines
+            .iter()
+            .filter_map(|(_, count)| count.as_ref().map(Count::bits))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Splitters shade from blue to red with the logarithm of the number of
+/// timelines they split; grey ones never split a beam and magenta ones are
+/// only reached through a loop.
+fn color(manifold: &Manifold, usage: &Usage, max_bits: u64, pos: Pos) -> Rgb {
+    let cell = manifold.cells[pos];
+    if cell.is_splitter() {
+        return match (&usage.timelines[pos], usage.hit[pos]) {
+            (Some(count), _) => {
+                let t = count.bits() as f64 / max_bits.max(1) as f64;
+                let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+                [
+                    mix(COLD[0], HOT[0]),
+                    mix(COLD[1], HOT[1]),
+                    mix(COLD[2], HOT[2]),
+                ]
+            }
+            (None, true) => LOOPING,
+            (None, false) => UNUSED,
+        };
+    }
+    match cell {
+        Cell::Start => START,
+        Cell::Empty if usage.lit[pos] => LIT,
+        Cell::Empty => EMPTY,
+        _ => WALL,
+    }
+}
+
+fn symbol(cell: Cell, lit: bool) -> char {
+    match cell {
+        Cell::Empty if lit => '|',
+        Cell::Empty => '.',
+        Cell::Start => 'S',
+        Cell::Splitter => '^',
+        Cell::SideSplitter => '-',
+        Cell::Mirror => '/',
+        Cell::BackMirror => '\\',
+        Cell::Absorber => '#',
+    }
+}
+
+/// The manifold with lit cells drawn as `|`. With `ansi`, lit cells and
+/// splitters are colored with 24-bit escape codes.
+pub fn render_text(manifold: &Manifold, usage: &Usage, ansi: bool) -> String {
+    let max_bits = usage.max_bits();
+    let mut out = String::new();
+    for row in 0..manifold.cells.height() {
+        for col in 0..manifold.cells.width() {
+            let pos = Pos::new(row, col);
+            let ch = symbol(manifold.cells[pos], usage.lit[pos]);
+            if ansi && ch != '.' {
+                let [r, g, b] = color(manifold, usage, max_bits, pos);
+                write!(out, "\x1b[38;2;{r};{g};{b}m{ch}\x1b[0m").unwrap();
+            } else {
+                out.push(ch);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+pub fn render_image(manifold: &Manifold, usage: &Usage, scale: usize) -> Image {
+    let max_bits = usage.max_bits();
+    let cells = &manifold.cells;
+    let mut image = Image::new(cells.width() * scale, cells.height() * scale, EMPTY);
+    for pos in cells.positions() {
+        image.fill_cell(
+            pos.col,
+            pos.row,
+            scale,
+            color(manifold, usage, max_bits, pos),
+        );
+    }
+    image
+}
+
+/// Every splitter with the number of timelines it split, followed by the
+/// splitters that never split a beam.
+pub fn splitter_report(manifold: &Manifold, usage: &Usage) -> String {
+    let splitters: Vec<_> = manifold
+        .cells
+        .iter()
+        .filter(|(_, cell)| cell.is_splitter())
+        .map(|(pos, _)| pos)
+        .collect();
+    let unused: Vec<_> = splitters.iter().filter(|&&pos| !usage.hit[pos]).collect();
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "splitters: {}, used: {}, never hit: {}",
+        splitters.len(),
+        splitters.len() - unused.len(),
+        unused.len()
+    )
+    .unwrap();
+    writeln!(out, "\nsplitter\ttimelines").unwrap();
+    for &pos in &splitters {
+        match (&usage.timelines[pos], usage.hit[pos]) {
+            (Some(count), _) => writeln!(out, "{pos}\t{count}").unwrap(),
+            (None, true) => writeln!(out, "{pos}\tunbounded (loop)").unwrap(),
+            (None, false) => {}
+        }
+    }
+    writeln!(out, "\nnever hit").unwrap();
+    for pos in unused {
+        writeln!(out, "{pos}").unwrap();
+    }
+    out
+}
@@ -0,0 +1,139 @@
+//! Row-by-row sweep for manifolds where every beam moves down, which only
+//! holds when there are no cells besides `.`, `S` and `^`. The beams of the
+//! current and next row live in two dense column arrays that are swapped and
+//! reused instead of reallocated. Beams spread at most one column per row,
+//! so only the columns between the outermost beams are scanned.
+
+use crate::count::Tally;
+use crate::manifold::{Cell, Manifold};
+use std::ops::Range;
+
+pub fn split_count(manifold: &Manifold) -> usize {
+    let grid = &manifold.cells;
+    let cols = grid.width();
+    let start = manifold.start;
+
+    let mut beams = vec![false; cols];
+    let mut next = vec![false; cols];
+    beams[start.col] = true;
+    let mut split_count = 0;
+
+    let mut span = start.col..start.col + 1;
+    for row in (start.row + 1)..grid.height() {
+        let cells = grid.row(row);
+        for (c, beam) in beams.iter_mut().enumerate().take(span.end).skip(span.start) {
+            if !std::mem::take(beam) {
+                continue;
+            }
+            if cells[c] == Cell::Splitter {
+                split_count += 1;
+                if c > 0 {
+                    next[c - 1] = true;
+                }
+                if c + 1 < cols {
+                    next[c + 1] = true;
+                }
+            } else {
+                next[c] = true;
+            }
+        }
+        span = widen(span, cols);
+        std::mem::swap(&mut beams, &mut next);
+    }
+
+    split_count
+}
+
+pub fn count_timelines<T: Tally>(manifold: &Manifold, one: T) -> T {
+    let grid = &manifold.cells;
+    let cols = grid.width();
+    let start = manifold.start;
+    let zero = one.zero();
+
+    let mut counts = vec![zero.clone(); cols];
+    let mut next = vec![zero.clone(); cols];
+    counts[start.col] = one;
+    // Timelines whose split branch left the grid at the side.
+    let mut ended = zero.clone();
+
+    let mut span = start.col..start.col + 1;
+    for row in (start.row + 1)..grid.height() {
+        let cells = grid.row(row);
+        for (c, slot) in counts
+            .iter_mut()
+            .enumerate()
+            .take(span.end)
+            .skip(span.start)
+        {
+            if slot.is_zero() {
+                continue;
+            }
+            let count = std::mem::replace(slot, zero.clone());
+            if cells[c] == Cell::Splitter {
+                match c {
+                    0 => ended.add(&count),
+                    _ => next[c - 1].add(&count),
+                }
+                if c + 1 < cols {
+                    deposit(&mut next[c + 1], count);
+                } else {
+                    ended.add(&count);
+                }
+            } else {
+                deposit(&mut next[c], count);
+            }
+        }
+        span = widen(span, cols);
+        std::mem::swap(&mut counts, &mut next);
+    }
+
+    counts.iter().fold(ended, |mut total, count| {
+        total.add(count);
+        total
+    })
+}
+
+/// Columns that may hold a beam one row below beams in `span`.
+fn widen(span: Range<usize>, cols: usize) -> Range<usize> {
+    span.start.saturating_sub(1)..(span.end + 1).min(cols)
+}
+
+/// Adds `count` into `slot`, moving it there when the slot is still empty.
+fn deposit<T: Tally>(slot: &mut T, count: T) {
+    if slot.is_zero() {
+        *slot = count;
+    } else {
+        slot.add(&count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_timelines, split_count};
+    use crate::bench::generate;
+    use crate::count::{Count, Modular};
+    use crate::reference;
+
+    #[test]
+    fn test_matches_reference() {
+        for (width, height, seed) in [(1, 6, 1), (7, 20, 2), (130, 60, 3), (257, 300, 4)] {
+            let manifold = generate(width, height, seed);
+            assert_eq!(
+                split_count(&manifold),
+                reference::split_count(&manifold),
+                "{width}x{height}"
+            );
+            assert_eq!(
+                count_timelines(&manifold, Count::one()),
+                reference::count_timelines(&manifold, Count::one()),
+                "{width}x{height}"
+            );
+            let one = Modular::one(1_000_000_007).unwrap();
+            assert_eq!(
+                count_timelines(&manifold, one),
+                reference::count_timelines(&manifold, one),
+                "{width}x{height}"
+            );
+        }
+    }
+}
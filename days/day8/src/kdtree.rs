@@ -0,0 +1,153 @@
+use crate::metric::Metric;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+const LEAF_SIZE: usize = 8;
+
+/// Entries of a neighbor search heap: a node is ordered by the smallest
+/// distance any point inside it could have, a point by its distance. At equal
+/// distances nodes come first, so a point is only reported once nothing left
+/// could beat it or tie with a smaller index.
+type Entry = Reverse<(u128, Kind, usize)>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Node,
+    Point,
+}
+
+struct Node {
+    lo: [i64; 3],
+    hi: [i64; 3],
+    /// Largest point index inside the node.
+    max_index: usize,
+    /// Positions in `order` of the node's points.
+    range: Range<usize>,
+    children: Option<(usize, usize)>,
+}
+
+/// k-d tree over 3D points, split on the widest axis at the median.
+pub struct KdTree {
+    points: Vec<[i64; 3]>,
+    metric: Metric,
+    order: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+impl KdTree {
+    pub fn new(points: Vec<[i64; 3]>, metric: Metric) -> Self {
+        let n = points.len();
+        let mut tree = KdTree {
+            points,
+            metric,
+            order: (0..n).collect(),
+            nodes: Vec::new(),
+        };
+        if n > 0 {
+            tree.build(0..n);
+        }
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    fn build(&mut self, range: Range<usize>) -> usize {
+        let slice = &mut self.order[range.clone()];
+        let mut lo = [i64::MAX; 3];
+        let mut hi = [i64::MIN; 3];
+        for &i in slice.iter() {
+            for axis in 0..3 {
+                lo[axis] = lo[axis].min(self.points[i][axis]);
+                hi[axis] = hi[axis].max(self.points[i][axis]);
+            }
+        }
+        let max_index = *slice.iter().max().unwrap();
+
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            lo,
+            hi,
+            max_index,
+            range: range.clone(),
+            children: None,
+        });
+        if range.len() > LEAF_SIZE {
+            let axis = (0..3)
+                .max_by_key(|&axis| hi[axis] as i128 - lo[axis] as i128)
+                .unwrap();
+            let mid = range.len() / 2;
+            let points = &self.points;
+            slice.select_nth_unstable_by_key(mid, |&i| points[i][axis]);
+            let left = self.build(range.start..range.start + mid);
+            let right = self.build(range.start + mid..range.end);
+            self.nodes[id].children = Some((left, right));
+        }
+        id
+    }
+
+    /// Smallest distance from `p` to any point in the node's box. Every
+    /// metric grows with the axis differences, so that is the distance to
+    /// the nearest point of the box.
+    fn lower_bound(&self, node: &Node, p: [i64; 3]) -> u128 {
+        let mut nearest = p;
+        for axis in 0..3 {
+            nearest[axis] = p[axis].clamp(node.lo[axis], node.hi[axis]);
+        }
+        self.metric.distance(p, nearest)
+    }
+
+    /// A search over the points with an index above `query`.
+    pub fn neighbors_after(&self, query: usize) -> Neighbors {
+        let mut heap = BinaryHeap::new();
+        if self
+            .nodes
+            .first()
+            .is_some_and(|root| root.max_index > query)
+        {
+            let bound = self.lower_bound(&self.nodes[0], self.points[query]);
+            heap.push(Reverse((bound, Kind::Node, 0)));
+        }
+        Neighbors { query, heap }
+    }
+
+    /// The next point of the search, nearest first; equal distances come out
+    /// in index order.
+    pub fn next_neighbor(&self, search: &mut Neighbors) -> Option<(u128, usize)> {
+        let p = self.points[search.query];
+        while let Some(Reverse((dist, kind, id))) = search.heap.pop() {
+            if kind == Kind::Point {
+                return Some((dist, id));
+            }
+            let node = &self.nodes[id];
+            match node.children {
+                Some((left, right)) => {
+                    for child in [left, right] {
+                        let child_node = &self.nodes[child];
+                        if child_node.max_index > search.query {
+                            let bound = self.lower_bound(child_node, p);
+                            search.heap.push(Reverse((bound, Kind::Node, child)));
+                        }
+                    }
+                }
+                None => {
+                    for &j in &self.order[node.range.clone()] {
+                        if j > search.query {
+                            let dist = self.metric.distance(p, self.points[j]);
+                            search.heap.push(Reverse((dist, Kind::Point, j)));
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// State of an incremental nearest-neighbor search in a `KdTree`.
+pub struct Neighbors {
+    query: usize,
+    heap: BinaryHeap<Entry>,
+}
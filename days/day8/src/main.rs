@@ -1,175 +1,335 @@
+mod kdtree;
+mod metric;
+mod network;
+mod pairs;
+mod points;
+
+use metric::{parse_weights, Metric};
+use network::{circuits, format_circuits, format_edges, newick, spanning_tree, ExportFormat};
+use pairs::Index;
+use points::{coordinates, parse_points, Point};
 use std::cmp::Reverse;
-use std::collections::HashMap;
 use std::io::{self, Read};
 
-#[derive(Clone)]
-struct Point {
-    x: i64,
-    y: i64,
-    z: i64,
-}
+/// Connects the `connections` closest pairs and multiplies the sizes of the
+/// `top` largest circuits, counting missing circuits as 1. Returns `None` if
+/// the product doesn't fit in a `u128`.
+fn solve_part1(
+    points: &[Point],
+    metric: Metric,
+    connections: usize,
+    top: usize,
+    index: Index,
+) -> Option<u128> {
+    let n = points.len();
+    if n == 0 {
+        return Some(0);
+    }
 
-#[derive(Clone)]
-struct UnionFind {
-    parent: Vec<usize>,
-    size: Vec<usize>,
+    let mut sizes: Vec<usize> = circuits(&coordinates(points), metric, connections, index)
+        .iter()
+        .map(Vec::len)
+        .collect();
+    sizes.sort_unstable_by_key(|&s| Reverse(s));
+    sizes
+        .iter()
+        .take(top)
+        .try_fold(1u128, |acc, &s| acc.checked_mul(s as u128))
 }
 
-impl UnionFind {
-    fn new(n: usize) -> Self {
-        UnionFind {
-            parent: (0..n).collect(),
-            size: vec![1; n],
-        }
+/// Product of the X coordinates of the last two boxes joined into a single
+/// circuit. Exact, since coordinates are within `MAX_COORDINATE`.
+fn solve_part2(points: &[Point], metric: Metric, index: Index) -> i128 {
+    let tree = spanning_tree(&coordinates(points), metric, index);
+    match tree.last() {
+        Some(&(_d, a, b)) => points[a].x as i128 * points[b].x as i128,
+        None => 0,
     }
+}
 
-    fn find(&mut self, x: usize) -> usize {
-        if self.parent[x] != x {
-            self.parent[x] = self.find(self.parent[x]);
-        }
-        self.parent[x]
-    }
+fn flag_value<'a>(flag: &str, value: Option<&'a String>) -> Result<&'a str, String> {
+    value
+        .map(String::as_str)
+        .ok_or_else(|| format!("{flag} needs a value"))
+}
 
-    fn union(&mut self, a: usize, b: usize) {
-        let mut ra = self.find(a);
-        let mut rb = self.find(b);
-        if ra == rb {
-            return;
-        }
-        if self.size[ra] < self.size[rb] {
-            std::mem::swap(&mut ra, &mut rb);
-        }
-        self.parent[rb] = ra;
-        self.size[ra] += self.size[rb];
-    }
+fn parse_count(flag: &str, value: Option<&String>) -> Result<usize, String> {
+    let value = flag_value(flag, value)?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{value}' for {flag}"))
+}
 
-    fn component_sizes(&mut self) -> Vec<usize> {
-        let mut counts: HashMap<usize, usize> = HashMap::new();
-        for i in 0..self.parent.len() {
-            let r = self.find(i);
-            *counts.entry(r).or_insert(0) += 1;
+struct Options {
+    connections: usize,
+    top: usize,
+    metric: Metric,
+    index: Index,
+    format: ExportFormat,
+}
+
+fn parse_options(command: &str, flags: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        connections: 1000,
+        top: 3,
+        metric: Metric::default(),
+        index: Index::KdTree,
+        format: ExportFormat::Csv,
+    };
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match (command, flag.as_str()) {
+            ("part1" | "circuits", "--connections") => {
+                options.connections = parse_count(flag, flags.next())?
+            }
+            ("part1", "--top") => options.top = parse_count(flag, flags.next())?,
+            (_, "--metric") => options.metric.kind = flag_value(flag, flags.next())?.parse()?,
+            (_, "--weights") => {
+                options.metric.weights = parse_weights(flag_value(flag, flags.next())?)?
+            }
+            ("mst" | "circuits", "--format") => {
+                options.format = flag_value(flag, flags.next())?.parse()?
+            }
+            (_, "--index") => options.index = flag_value(flag, flags.next())?.parse()?,
+            _ => return Err(format!("unknown flag '{flag}' for {command}")),
         }
-        counts.values().cloned().collect()
     }
+    Ok(options)
 }
 
-fn parse_points(input: &str) -> Vec<Point> {
-    input
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .map(|line| {
-            let nums: Vec<i64> = line
-                .split(',')
-                .map(|s| s.trim().parse::<i64>().expect("invalid number"))
-                .collect();
-            Point {
-                x: nums[0],
-                y: nums[1],
-                z: nums[2],
-            }
-        })
-        .collect()
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {program} <part1|part2|mst|dendrogram|circuits> [--index kdtree|brute]");
+    eprintln!("       [--metric euclidean|manhattan|chebyshev] [--weights X,Y,Z]");
+    eprintln!("       part1: [--connections N] [--top K]");
+    eprintln!("       mst: [--format csv|json]");
+    eprintln!("       circuits: [--connections N] [--format csv|json]");
+    eprintln!("Example: {program} part1 < input.txt");
+    std::process::exit(1);
 }
 
-fn solve_part1(input: &str) -> u128 {
-    const EDGES_TO_ADD: usize = 1000;
-    let points = parse_points(input);
-    let n = points.len();
-    if n == 0 {
-        return 0;
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let commands = ["part1", "part2", "mst", "dendrogram", "circuits"];
+    if args.len() < 2 || !commands.contains(&args[1].as_str()) {
+        usage(&args[0]);
     }
 
-    let mut edges: Vec<(u64, usize, usize)> = Vec::with_capacity(n.saturating_mul(n) / 2);
-    for i in 0..n {
-        for j in (i + 1)..n {
-            let dx = points[i].x - points[j].x;
-            let dy = points[i].y - points[j].y;
-            let dz = points[i].z - points[j].z;
-            let dist2 = (dx * dx + dy * dy + dz * dz) as u64;
-            edges.push((dist2, i, j));
-        }
-    }
+    let command = args[1].as_str();
+    let options = parse_options(command, &args[2..]).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        usage(&args[0]);
+    });
 
-    edges.sort_unstable_by_key(|e| e.0);
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .expect("failed to read input");
 
-    let mut uf = UnionFind::new(n);
-    for &( _d, a, b) in edges.iter().take(EDGES_TO_ADD) {
-        uf.union(a, b);
+    let points = parse_points(&input).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    });
+    if let Err(err) = options.metric.check(&coordinates(&points)) {
+        eprintln!("{err}");
+        std::process::exit(1);
     }
 
-    let mut sizes = uf.component_sizes();
-    sizes.sort_unstable_by_key(|&s| Reverse(s));
-    while sizes.len() < 3 {
-        sizes.push(1);
+    let metric = options.metric;
+    match command {
+        "part1" => {
+            let product = solve_part1(
+                &points,
+                metric,
+                options.connections,
+                options.top,
+                options.index,
+            );
+            let product = product.unwrap_or_else(|| {
+                eprintln!(
+                    "the product of the {} largest circuits overflows u128",
+                    options.top
+                );
+                std::process::exit(1);
+            });
+            println!("{product}");
+        }
+        "part2" => println!("{}", solve_part2(&points, metric, options.index)),
+        "mst" => {
+            let tree = spanning_tree(&coordinates(&points), metric, options.index);
+            print!("{}", format_edges(&tree, metric, options.format));
+        }
+        "dendrogram" => {
+            let tree = spanning_tree(&coordinates(&points), metric, options.index);
+            println!("{}", newick(points.len(), &tree, metric));
+        }
+        _ => {
+            let circuits = circuits(
+                &coordinates(&points),
+                metric,
+                options.connections,
+                options.index,
+            );
+            print!("{}", format_circuits(&circuits, options.format));
+        }
     }
-    let product = sizes.iter().take(3).fold(1u128, |acc, &s| acc * s as u128);
-    product
 }
 
-fn solve_part2(input: &str) -> u128 {
-    let points = parse_points(input);
-    let n = points.len();
-    if n == 0 {
-        return 0;
-    }
+#[cfg(test)]
+mod tests {
+    use super::{parse_options, parse_points, solve_part1, solve_part2, Index, Metric};
+    use crate::metric::MetricKind;
+    use crate::network::spanning_tree;
+    use crate::points::{coordinates, MAX_COORDINATE};
 
-    let mut edges: Vec<(u64, usize, usize)> = Vec::with_capacity(n.saturating_mul(n) / 2);
-    for i in 0..n {
-        for j in (i + 1)..n {
-            let dx = points[i].x - points[j].x;
-            let dy = points[i].y - points[j].y;
-            let dz = points[i].z - points[j].z;
-            let dist2 = (dx * dx + dy * dy + dz * dz) as u64;
-            edges.push((dist2, i, j));
+    const EXAMPLE: &str = "\
+162,817,812
+57,618,57
+906,360,560
+592,479,940
+352,342,300
+466,668,158
+542,29,236
+431,825,988
+739,650,466
+52,470,668
+216,146,977
+819,987,18
+117,168,530
+805,96,715
+346,949,466
+970,615,88
+941,993,340
+862,61,35
+984,92,344
+425,690,689
+";
+
+    #[test]
+    fn test_example_part1() {
+        let points = parse_points(EXAMPLE).unwrap();
+        for index in [Index::KdTree, Index::Brute] {
+            assert_eq!(
+                solve_part1(&points, Metric::default(), 10, 3, index),
+                Some(40)
+            );
         }
     }
 
-    edges.sort_unstable_by_key(|e| e.0);
-
-    let mut uf = UnionFind::new(n);
-    let mut last_edge = None;
-    
-    for &(_d, a, b) in edges.iter() {
-        let root_a = uf.find(a);
-        let root_b = uf.find(b);
-        if root_a != root_b {
-            uf.union(a, b);
-            last_edge = Some((a, b));
-            
-            let root = uf.find(a);
-            if uf.size[root] == n {
-                break;
-            }
+    #[test]
+    fn test_example_part2() {
+        let points = parse_points(EXAMPLE).unwrap();
+        for index in [Index::KdTree, Index::Brute] {
+            assert_eq!(solve_part2(&points, Metric::default(), index), 25272);
         }
     }
 
-    if let Some((a, b)) = last_edge {
-        (points[a].x * points[b].x) as u128
-    } else {
-        0
+    #[test]
+    fn test_connections_and_top() {
+        let points = parse_points(EXAMPLE).unwrap();
+        // No connections leave 20 circuits of one box each.
+        assert_eq!(
+            solve_part1(&points, Metric::default(), 0, 3, Index::KdTree),
+            Some(1)
+        );
+        // Enough connections join everything into one circuit.
+        assert_eq!(
+            solve_part1(&points, Metric::default(), 190, 3, Index::KdTree),
+            Some(20)
+        );
+        assert_eq!(
+            solve_part1(&points, Metric::default(), 10, 1, Index::KdTree),
+            Some(5)
+        );
+        assert_eq!(
+            solve_part1(&points, Metric::default(), 10, 0, Index::KdTree),
+            Some(1)
+        );
     }
-}
 
-fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 || (args[1] != "part1" && args[1] != "part2") {
-        eprintln!("Usage: {} <part1|part2>", args[0]);
-        eprintln!("Example: {} part1 < input.txt", args[0]);
-        std::process::exit(1);
+    #[test]
+    fn test_product_overflow() {
+        // 130 pairs of boxes one unit apart, far from every other pair.
+        let input: String = (0..130)
+            .map(|i| format!("{0},0,0\n{1},0,0\n", i * 100, i * 100 + 1))
+            .collect();
+        let points = parse_points(&input).unwrap();
+        assert_eq!(
+            solve_part1(&points, Metric::default(), 130, 127, Index::KdTree),
+            Some(1 << 127)
+        );
+        assert_eq!(
+            solve_part1(&points, Metric::default(), 130, 128, Index::KdTree),
+            None
+        );
     }
 
-    let part = &args[1];
+    #[test]
+    fn test_coordinate_limits() {
+        let m = MAX_COORDINATE;
+        let input = format!("-{m},-{m},-{m}\n{m},{m},{m}\n{m},{m},-{m}\n");
+        let points = parse_points(&input).unwrap();
+        for index in [Index::KdTree, Index::Brute] {
+            // The last two boxes differ only in Z, by 2^63; the first box
+            // then joins at 2^127 rather than 3 * 2^126.
+            assert_eq!(
+                spanning_tree(&coordinates(&points), Metric::default(), index),
+                vec![(1 << 126, 1, 2), (1 << 127, 0, 2)]
+            );
+            assert_eq!(solve_part2(&points, Metric::default(), index), -(1 << 124));
+            assert_eq!(
+                solve_part1(&points, Metric::default(), 1, 3, index),
+                Some(2)
+            );
+        }
 
-    let mut input = String::new();
-    io::stdin()
-        .read_to_string(&mut input)
-        .expect("failed to read input");
+        let points = parse_points(&format!("-{m},0,0\n-{m},1,0\n")).unwrap();
+        assert_eq!(
+            solve_part2(&points, Metric::default(), Index::KdTree),
+            1 << 124
+        );
+    }
 
-    let answer = if part == "part1" {
-        solve_part1(&input)
-    } else {
-        solve_part2(&input)
-    };
+    #[test]
+    fn test_metrics_on_example() {
+        let points = parse_points(EXAMPLE).unwrap();
+        for kind in [MetricKind::Manhattan, MetricKind::Chebyshev] {
+            for weights in [[1; 3], [1, 1, 3]] {
+                let metric = Metric { kind, weights };
+                assert_eq!(
+                    solve_part1(&points, metric, 10, 3, Index::KdTree),
+                    solve_part1(&points, metric, 10, 3, Index::Brute)
+                );
+                assert_eq!(
+                    solve_part2(&points, metric, Index::KdTree),
+                    solve_part2(&points, metric, Index::Brute)
+                );
+            }
+        }
+    }
 
-    println!("{answer}");
+    #[test]
+    fn test_flags_are_checked_per_command() {
+        let flags = |flags: &[&str]| flags.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        let accepts = |command, args: &[&str]| parse_options(command, &flags(args)).is_ok();
+        assert!(accepts("part1", &["--connections", "5", "--top", "2"]));
+        assert!(accepts(
+            "circuits",
+            &["--connections", "5", "--format", "json"]
+        ));
+        assert!(accepts(
+            "mst",
+            &["--format", "json", "--metric", "manhattan"]
+        ));
+        assert!(!accepts("part2", &["--connections", "5"]));
+        assert!(!accepts("part2", &["--top", "2"]));
+        assert!(!accepts("part2", &["--format", "json"]));
+        assert!(!accepts("circuits", &["--top", "2"]));
+        assert!(!accepts("dendrogram", &["--format", "json"]));
+        for command in ["part1", "part2", "mst", "dendrogram", "circuits"] {
+            assert!(accepts(
+                command,
+                &["--index", "brute", "--weights", "1,1,2"]
+            ));
+        }
+    }
 }
@@ -0,0 +1,177 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricKind {
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+}
+
+impl FromStr for MetricKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "euclidean" => Ok(MetricKind::Euclidean),
+            "manhattan" => Ok(MetricKind::Manhattan),
+            "chebyshev" => Ok(MetricKind::Chebyshev),
+            _ => Err(format!(
+                "unknown metric '{s}', expected 'euclidean', 'manhattan' or 'chebyshev'"
+            )),
+        }
+    }
+}
+
+/// How far apart two boxes are: each axis difference is multiplied by the
+/// axis weight, then combined by `kind`. Euclidean distances are kept
+/// squared so every distance is an exact integer; pairs at equal distances
+/// are ordered by their indices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Metric {
+    pub kind: MetricKind,
+    pub weights: [u64; 3],
+}
+
+impl Default for Metric {
+    fn default() -> Self {
+        Metric {
+            kind: MetricKind::Euclidean,
+            weights: [1; 3],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DistanceOverflow {
+    pub metric: Metric,
+}
+
+impl fmt::Display for DistanceOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Metric { kind, weights } = self.metric;
+        write!(
+            f,
+            "{kind:?} distances with weights {weights:?} overflow u128 for these boxes"
+        )
+    }
+}
+
+impl std::error::Error for DistanceOverflow {}
+
+impl Metric {
+    /// `None` if the distance doesn't fit in a `u128`.
+    pub fn checked_distance(&self, a: [i64; 3], b: [i64; 3]) -> Option<u128> {
+        let mut total: u128 = 0;
+        for axis in 0..3 {
+            let d = (a[axis].abs_diff(b[axis]) as u128).checked_mul(self.weights[axis] as u128)?;
+            total = match self.kind {
+                MetricKind::Euclidean => total.checked_add(d.checked_mul(d)?)?,
+                MetricKind::Manhattan => total.checked_add(d)?,
+                MetricKind::Chebyshev => total.max(d),
+            };
+        }
+        Some(total)
+    }
+
+    /// Distance between two boxes of a set that passed `check`.
+    pub fn distance(&self, a: [i64; 3], b: [i64; 3]) -> u128 {
+        self.checked_distance(a, b)
+            .expect("distance overflow in a checked point set")
+    }
+
+    /// Makes sure no distance between `points`, and no distance from one of
+    /// them to their bounding box, overflows. Distances only grow with the
+    /// axis differences, so the opposite corners of the box are the worst
+    /// case.
+    pub fn check(&self, points: &[[i64; 3]]) -> Result<(), DistanceOverflow> {
+        let mut lo = [i64::MAX; 3];
+        let mut hi = [i64::MIN; 3];
+        for p in points {
+            for axis in 0..3 {
+                lo[axis] = lo[axis].min(p[axis]);
+                hi[axis] = hi[axis].max(p[axis]);
+            }
+        }
+        if points.is_empty() || self.checked_distance(lo, hi).is_some() {
+            Ok(())
+        } else {
+            Err(DistanceOverflow { metric: *self })
+        }
+    }
+
+    /// The distance in the metric's own units, for reports.
+    pub fn length(&self, distance: u128) -> f64 {
+        match self.kind {
+            MetricKind::Euclidean => (distance as f64).sqrt(),
+            MetricKind::Manhattan | MetricKind::Chebyshev => distance as f64,
+        }
+    }
+}
+
+/// Parses `X,Y,Z` axis weights.
+pub fn parse_weights(s: &str) -> Result<[u64; 3], String> {
+    let weights: Vec<u64> = s
+        .split(',')
+        .map(|w| w.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid weights '{s}', expected X,Y,Z"))?;
+    weights
+        .try_into()
+        .map_err(|_| format!("invalid weights '{s}', expected X,Y,Z"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_weights, DistanceOverflow, Metric, MetricKind};
+    use crate::points::MAX_COORDINATE;
+
+    #[test]
+    fn test_metrics() {
+        let (a, b) = ([1, -2, 3], [4, 2, -9]);
+        let metric = |kind, weights| Metric { kind, weights };
+        assert_eq!(metric(MetricKind::Euclidean, [1; 3]).distance(a, b), 169);
+        assert_eq!(metric(MetricKind::Manhattan, [1; 3]).distance(a, b), 19);
+        assert_eq!(metric(MetricKind::Chebyshev, [1; 3]).distance(a, b), 12);
+        assert_eq!(metric(MetricKind::Euclidean, [2, 1, 0]).distance(a, b), 52);
+        assert_eq!(metric(MetricKind::Manhattan, [1, 1, 3]).distance(a, b), 43);
+        assert_eq!(metric(MetricKind::Chebyshev, [5, 1, 1]).distance(a, b), 15);
+        assert_eq!(metric(MetricKind::Euclidean, [1; 3]).length(169), 13.0);
+        assert_eq!(metric(MetricKind::Manhattan, [1; 3]).length(19), 19.0);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let m = MAX_COORDINATE;
+        let points = [[-m, -m, -m], [m, m, m]];
+        assert_eq!(Metric::default().check(&points), Ok(()));
+        assert_eq!(Metric::default().distance(points[0], points[1]), 3 << 126);
+        let heavy = Metric {
+            kind: MetricKind::Euclidean,
+            weights: [1, 1, 2],
+        };
+        assert_eq!(
+            heavy.check(&points),
+            Err(DistanceOverflow { metric: heavy })
+        );
+        assert_eq!(heavy.check(&[]), Ok(()));
+        let mut manhattan = Metric {
+            kind: MetricKind::Manhattan,
+            weights: [u64::MAX, 1, 1],
+        };
+        assert_eq!(manhattan.check(&points), Ok(()));
+        assert_eq!(
+            manhattan.checked_distance([0; 3], [1; 3]),
+            Some(u64::MAX as u128 + 2)
+        );
+        manhattan.weights = [u64::MAX; 3];
+        assert!(manhattan.check(&points).is_err());
+    }
+
+    #[test]
+    fn test_parse_weights() {
+        assert_eq!(parse_weights("1, 1,3"), Ok([1, 1, 3]));
+        assert!(parse_weights("1,1").is_err());
+        assert!(parse_weights("1,-1,1").is_err());
+    }
+}
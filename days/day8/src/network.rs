@@ -0,0 +1,273 @@
+use crate::metric::Metric;
+use crate::pairs::{closest_pairs, Edge, Index};
+use common::union_find::UnionFind;
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => Err(format!("unknown format '{s}', expected 'csv' or 'json'")),
+        }
+    }
+}
+
+/// Kruskal's algorithm over the pairs in order: the edges that joined two
+/// circuits, in the order they were added. Stops as soon as every box is in
+/// one circuit.
+pub fn spanning_tree(points: &[[i64; 3]], metric: Metric, index: Index) -> Vec<Edge> {
+    let mut uf = UnionFind::new(points.len());
+    let mut tree = Vec::with_capacity(points.len().saturating_sub(1));
+    for (dist, a, b) in closest_pairs(points.to_vec(), metric, index) {
+        if uf.components() <= 1 {
+            break;
+        }
+        if uf.union(a, b) {
+            tree.push((dist, a, b));
+        }
+    }
+    tree
+}
+
+/// The circuits after connecting the `connections` closest pairs, ordered by
+/// their smallest box, with boxes in increasing order.
+pub fn circuits(
+    points: &[[i64; 3]],
+    metric: Metric,
+    connections: usize,
+    index: Index,
+) -> Vec<Vec<usize>> {
+    let mut uf = UnionFind::new(points.len());
+    for (_d, a, b) in closest_pairs(points.to_vec(), metric, index).take(connections) {
+        uf.union(a, b);
+    }
+    uf.sets()
+}
+
+/// Edges with the boxes as 0-based input line numbers, the exact distance
+/// the edges are ordered by (squared for Euclidean) as `cost`, and the
+/// distance itself.
+pub fn format_edges(edges: &[Edge], metric: Metric, format: ExportFormat) -> String {
+    let mut out = String::new();
+    match format {
+        ExportFormat::Csv => {
+            writeln!(out, "a,b,cost,distance").unwrap();
+            for &(dist, a, b) in edges {
+                writeln!(out, "{a},{b},{dist},{}", metric.length(dist)).unwrap();
+            }
+        }
+        ExportFormat::Json => {
+            let items: Vec<String> = edges
+                .iter()
+                .map(|&(dist, a, b)| {
+                    format!(
+                        "  {{\"a\": {a}, \"b\": {b}, \"cost\": {dist}, \"distance\": {}}}",
+                        metric.length(dist)
+                    )
+                })
+                .collect();
+            writeln!(out, "[\n{}\n]", items.join(",\n")).unwrap();
+        }
+    }
+    out
+}
+
+pub fn format_circuits(circuits: &[Vec<usize>], format: ExportFormat) -> String {
+    let mut out = String::new();
+    match format {
+        ExportFormat::Csv => {
+            writeln!(out, "box,circuit,size").unwrap();
+            let mut rows: Vec<(usize, usize, usize)> = circuits
+                .iter()
+                .enumerate()
+                .flat_map(|(c, members)| members.iter().map(move |&b| (b, c, members.len())))
+                .collect();
+            rows.sort_unstable();
+            for (b, c, size) in rows {
+                writeln!(out, "{b},{c},{size}").unwrap();
+            }
+        }
+        ExportFormat::Json => {
+            let items: Vec<String> = circuits
+                .iter()
+                .map(|members| {
+                    let boxes: Vec<String> = members.iter().map(usize::to_string).collect();
+                    format!("  [{}]", boxes.join(", "))
+                })
+                .collect();
+            writeln!(out, "[\n{}\n]", items.join(",\n")).unwrap();
+        }
+    }
+    out
+}
+
+/// The single-linkage clustering of `n` boxes in Newick format. Leaves are
+/// labelled with their 0-based line numbers, and every merge sits at the
+/// distance of the spanning tree edge that caused it, so branch lengths are
+/// differences of merge distances. The tree is written without recursion,
+/// since a chain of boxes makes it as deep as it is wide.
+pub fn newick(n: usize, tree: &[Edge], metric: Metric) -> String {
+    enum Visit {
+        Open(usize, f64),
+        Comma,
+        Close(usize, f64),
+    }
+
+    if n == 0 {
+        return ";".to_string();
+    }
+    // Nodes below `n` are the boxes; merge `k` is node `n + k`.
+    let mut children = Vec::with_capacity(tree.len());
+    let mut heights = vec![0.0; n];
+    let mut uf = UnionFind::new(n);
+    let mut cluster: Vec<usize> = (0..n).collect();
+    for &(dist, a, b) in tree {
+        let (ra, rb) = (uf.find(a), uf.find(b));
+        let node = n + children.len();
+        children.push((cluster[ra], cluster[rb]));
+        heights.push(metric.length(dist));
+        uf.union(ra, rb);
+        cluster[uf.find(ra)] = node;
+    }
+
+    let mut out = String::new();
+    let roots: Vec<usize> = (0..n).filter(|&x| uf.find(x) == x).collect();
+    // A forest, from a tree that isn't spanning, gets a root joining every
+    // part at the height of its highest merge.
+    let top = roots
+        .iter()
+        .map(|&r| heights[cluster[r]])
+        .fold(0.0, f64::max);
+    if roots.len() > 1 {
+        out.push('(');
+    }
+    for (k, &r) in roots.iter().enumerate() {
+        if k > 0 {
+            out.push(',');
+        }
+        let mut stack = vec![Visit::Open(cluster[r], top)];
+        while let Some(visit) = stack.pop() {
+            match visit {
+                Visit::Open(node, parent) if node >= n => {
+                    let (left, right) = children[node - n];
+                    out.push('(');
+                    stack.push(Visit::Close(node, parent));
+                    stack.push(Visit::Open(right, heights[node]));
+                    stack.push(Visit::Comma);
+                    stack.push(Visit::Open(left, heights[node]));
+                }
+                Visit::Open(node, parent) => {
+                    write!(out, "{node}").unwrap();
+                    if roots.len() > 1 || node != cluster[r] {
+                        write!(out, ":{}", parent - heights[node]).unwrap();
+                    }
+                }
+                Visit::Comma => out.push(','),
+                Visit::Close(node, parent) => {
+                    out.push(')');
+                    if roots.len() > 1 || node != cluster[r] {
+                        write!(out, ":{}", parent - heights[node]).unwrap();
+                    }
+                }
+            }
+        }
+    }
+    if roots.len() > 1 {
+        out.push(')');
+    }
+    out.push(';');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{circuits, format_circuits, format_edges, newick, spanning_tree, ExportFormat};
+    use crate::metric::{Metric, MetricKind};
+    use crate::pairs::Index;
+
+    const LINE: [[i64; 3]; 4] = [[0, 0, 0], [3, 0, 0], [4, 0, 0], [10, 0, 0]];
+
+    #[test]
+    fn test_spanning_tree() {
+        let euclidean = Metric::default();
+        let tree = spanning_tree(&LINE, euclidean, Index::KdTree);
+        assert_eq!(tree, vec![(1, 1, 2), (9, 0, 1), (36, 2, 3)]);
+        assert_eq!(tree, spanning_tree(&LINE, euclidean, Index::Brute));
+        assert!(spanning_tree(&LINE[..1], euclidean, Index::KdTree).is_empty());
+        assert_eq!(
+            format_edges(&tree[1..2], euclidean, ExportFormat::Csv),
+            "a,b,cost,distance\n0,1,9,3\n"
+        );
+        assert_eq!(
+            format_edges(&tree[1..2], euclidean, ExportFormat::Json),
+            "[\n  {\"a\": 0, \"b\": 1, \"cost\": 9, \"distance\": 3}\n]\n"
+        );
+
+        let manhattan = Metric {
+            kind: MetricKind::Manhattan,
+            weights: [1; 3],
+        };
+        let tree = spanning_tree(&LINE, manhattan, Index::KdTree);
+        assert_eq!(tree, vec![(1, 1, 2), (3, 0, 1), (6, 2, 3)]);
+        assert_eq!(
+            format_edges(&tree[1..2], manhattan, ExportFormat::Csv),
+            "a,b,cost,distance\n0,1,3,3\n"
+        );
+    }
+
+    #[test]
+    fn test_weighted_axes() {
+        // A weight of 3 on Z makes the vertical neighbor the farthest.
+        let points = [[0, 0, 0], [0, 0, 2], [4, 0, 0], [0, 5, 0]];
+        let plain = Metric {
+            kind: MetricKind::Chebyshev,
+            weights: [1; 3],
+        };
+        let weighted = Metric {
+            weights: [1, 1, 3],
+            ..plain
+        };
+        assert_eq!(
+            spanning_tree(&points, plain, Index::KdTree),
+            vec![(2, 0, 1), (4, 0, 2), (5, 0, 3)]
+        );
+        assert_eq!(
+            spanning_tree(&points, weighted, Index::KdTree),
+            vec![(4, 0, 2), (5, 0, 3), (6, 0, 1)]
+        );
+    }
+
+    #[test]
+    fn test_circuits() {
+        let circuits = circuits(&LINE, Metric::default(), 2, Index::KdTree);
+        assert_eq!(circuits, vec![vec![0, 1, 2], vec![3]]);
+        assert_eq!(
+            format_circuits(&circuits, ExportFormat::Csv),
+            "box,circuit,size\n0,0,3\n1,0,3\n2,0,3\n3,1,1\n"
+        );
+        assert_eq!(
+            format_circuits(&circuits, ExportFormat::Json),
+            "[\n  [0, 1, 2],\n  [3]\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_newick() {
+        let metric = Metric::default();
+        let tree = spanning_tree(&LINE, metric, Index::KdTree);
+        assert_eq!(newick(4, &tree, metric), "((0:3,(1:1,2:1):2):3,3:6);");
+        assert_eq!(newick(4, &tree[..2], metric), "((0:3,(1:1,2:1):2):0,3:3);");
+        assert_eq!(newick(1, &[], metric), "0;");
+        assert_eq!(newick(0, &[], metric), ";");
+    }
+}
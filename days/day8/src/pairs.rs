@@ -0,0 +1,158 @@
+use crate::kdtree::{KdTree, Neighbors};
+use crate::metric::Metric;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::str::FromStr;
+
+/// A candidate connection `(distance, i, j)` with `i < j`, the distance as
+/// given by `Metric::distance`.
+pub type Edge = (u128, usize, usize);
+
+/// How the pairs are enumerated; both give the same sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Index {
+    KdTree,
+    Brute,
+}
+
+impl FromStr for Index {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kdtree" => Ok(Index::KdTree),
+            "brute" => Ok(Index::Brute),
+            _ => Err(format!("unknown index '{s}', expected 'kdtree' or 'brute'")),
+        }
+    }
+}
+
+/// Every pair of points ordered by `(distance, i, j)`. The points must have
+/// passed `metric.check`.
+pub fn closest_pairs(
+    points: Vec<[i64; 3]>,
+    metric: Metric,
+    index: Index,
+) -> Box<dyn Iterator<Item = Edge>> {
+    match index {
+        Index::KdTree => Box::new(ClosestPairs::new(KdTree::new(points, metric))),
+        Index::Brute => Box::new(all_pairs(&points, metric).into_iter()),
+    }
+}
+
+/// Every pair, sorted. Quadratic in time and memory.
+fn all_pairs(points: &[[i64; 3]], metric: Metric) -> Vec<Edge> {
+    let n = points.len();
+    let mut edges: Vec<Edge> = Vec::with_capacity(n.saturating_mul(n) / 2);
+    for i in 0..n {
+        for j in (i + 1)..n {
+            edges.push((metric.distance(points[i], points[j]), i, j));
+        }
+    }
+    edges.sort_unstable();
+    edges
+}
+
+/// The same sequence as `all_pairs`, produced lazily: every point has a
+/// nearest-neighbor search over the points after it, and a heap holds the
+/// next pair from each search.
+struct ClosestPairs {
+    tree: KdTree,
+    searches: Vec<Neighbors>,
+    heap: BinaryHeap<Reverse<Edge>>,
+}
+
+impl ClosestPairs {
+    fn new(tree: KdTree) -> Self {
+        let mut searches: Vec<Neighbors> =
+            (0..tree.len()).map(|i| tree.neighbors_after(i)).collect();
+        let heap = searches
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, search)| {
+                let (dist, j) = tree.next_neighbor(search)?;
+                Some(Reverse((dist, i, j)))
+            })
+            .collect();
+        ClosestPairs {
+            tree,
+            searches,
+            heap,
+        }
+    }
+}
+
+impl Iterator for ClosestPairs {
+    type Item = Edge;
+
+    fn next(&mut self) -> Option<Edge> {
+        let Reverse(edge) = self.heap.pop()?;
+        let i = edge.1;
+        if let Some((dist, j)) = self.tree.next_neighbor(&mut self.searches[i]) {
+            self.heap.push(Reverse((dist, i, j)));
+        }
+        Some(edge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{all_pairs, ClosestPairs, Edge};
+    use crate::kdtree::KdTree;
+    use crate::metric::{Metric, MetricKind};
+    use crate::points::MAX_COORDINATE;
+    use common::rng::XorShift;
+
+    fn points(n: usize, spread: i64, seed: u64) -> Vec<[i64; 3]> {
+        let mut rng = XorShift::new(seed);
+        let mut next = || rng.below(2 * spread as u64 + 1) as i64 - spread;
+        (0..n).map(|_| [next(), next(), next()]).collect()
+    }
+
+    fn lazy(points: &[[i64; 3]], metric: Metric) -> Vec<Edge> {
+        ClosestPairs::new(KdTree::new(points.to_vec(), metric)).collect()
+    }
+
+    #[test]
+    fn test_kdtree_matches_brute_force() {
+        let metrics = [
+            (MetricKind::Euclidean, [1; 3]),
+            (MetricKind::Manhattan, [1; 3]),
+            (MetricKind::Chebyshev, [1; 3]),
+            (MetricKind::Euclidean, [1, 1, 3]),
+            (MetricKind::Manhattan, [2, 0, 5]),
+            (MetricKind::Chebyshev, [1, 4, 1]),
+        ];
+        for (n, spread, seed) in [(0, 5, 1), (1, 5, 2), (2, 5, 3), (60, 3, 4), (300, 1000, 5)] {
+            let points = points(n, spread, seed);
+            for (kind, weights) in metrics {
+                let metric = Metric { kind, weights };
+                assert_eq!(
+                    lazy(&points, metric),
+                    all_pairs(&points, metric),
+                    "n = {n}, spread = {spread}, {metric:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_extreme_coordinates() {
+        let m = MAX_COORDINATE;
+        let mut points = vec![[0, 0, 0], [1, -1, m], [-m, m - 1, 0]];
+        for corner in 0..8 {
+            points.push([0, 1, 2].map(|axis| if corner >> axis & 1 == 1 { m } else { -m }));
+        }
+        let brute = all_pairs(&points, Metric::default());
+        assert_eq!(brute[brute.len() - 1].0, 3 << 126);
+        assert_eq!(lazy(&points, Metric::default()), brute);
+    }
+
+    #[test]
+    fn test_duplicate_points() {
+        let points = vec![[1, 1, 1]; 20];
+        let edges = lazy(&points, Metric::default());
+        assert_eq!(edges.len(), 190);
+        assert_eq!(edges, all_pairs(&points, Metric::default()));
+    }
+}
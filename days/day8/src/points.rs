@@ -0,0 +1,127 @@
+use std::fmt;
+
+/// Largest coordinate magnitude. Differences then stay below 2^63, so a
+/// squared distance is below 3 * 2^126 and always fits in a `u128`.
+pub const MAX_COORDINATE: i64 = 1 << 62;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Point {
+    pub fn coordinates(self) -> [i64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PointError {
+    InvalidNumber { line: usize, token: String },
+    WrongDimension { line: usize, found: usize },
+    OutOfRange { line: usize, value: i128 },
+}
+
+impl fmt::Display for PointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointError::InvalidNumber { line, token } => {
+                write!(f, "line {line}: invalid number {token:?}")
+            }
+            PointError::WrongDimension { line, found } => {
+                write!(f, "line {line}: expected 3 coordinates, found {found}")
+            }
+            PointError::OutOfRange { line, value } => write!(
+                f,
+                "line {line}: coordinate {value} is outside ±{MAX_COORDINATE}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PointError {}
+
+/// One `X,Y,Z` point per non-blank line; `line` in errors is 1-based.
+pub fn parse_points(input: &str) -> Result<Vec<Point>, PointError> {
+    let mut points = Vec::new();
+    for (i, text) in input.lines().enumerate() {
+        if text.trim().is_empty() {
+            continue;
+        }
+        let line = i + 1;
+        let tokens: Vec<&str> = text.split(',').map(str::trim).collect();
+        if tokens.len() != 3 {
+            return Err(PointError::WrongDimension {
+                line,
+                found: tokens.len(),
+            });
+        }
+        let mut coords = [0; 3];
+        for (coord, token) in coords.iter_mut().zip(tokens) {
+            let value: i128 = token.parse().map_err(|_| PointError::InvalidNumber {
+                line,
+                token: token.to_string(),
+            })?;
+            if value.unsigned_abs() > MAX_COORDINATE as u128 {
+                return Err(PointError::OutOfRange { line, value });
+            }
+            *coord = value as i64;
+        }
+        let [x, y, z] = coords;
+        points.push(Point { x, y, z });
+    }
+    Ok(points)
+}
+
+pub fn coordinates(points: &[Point]) -> Vec<[i64; 3]> {
+    points.iter().map(|p| p.coordinates()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_points, Point, PointError, MAX_COORDINATE};
+
+    #[test]
+    fn test_parse() {
+        let points =
+            parse_points("\n1,-2, 3\n\n-4611686018427387904,0,4611686018427387904\n").unwrap();
+        assert_eq!(
+            points,
+            vec![
+                Point { x: 1, y: -2, z: 3 },
+                Point {
+                    x: -MAX_COORDINATE,
+                    y: 0,
+                    z: MAX_COORDINATE
+                }
+            ]
+        );
+        assert_eq!(
+            parse_points("1,2,3\n1,2\n"),
+            Err(PointError::WrongDimension { line: 2, found: 2 })
+        );
+        assert_eq!(
+            parse_points("1,x,3\n"),
+            Err(PointError::InvalidNumber {
+                line: 1,
+                token: "x".to_string()
+            })
+        );
+        assert_eq!(
+            parse_points("0,0,4611686018427387905\n"),
+            Err(PointError::OutOfRange {
+                line: 1,
+                value: (1 << 62) + 1
+            })
+        );
+        assert_eq!(
+            parse_points("0,-99999999999999999999,0\n"),
+            Err(PointError::OutOfRange {
+                line: 1,
+                value: -99999999999999999999
+            })
+        );
+    }
+}
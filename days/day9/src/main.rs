@@ -1,6 +1,7 @@
+use std::fmt;
 use std::io::{self, Read};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 struct Point {
     x: i64,
     y: i64,
@@ -25,16 +26,38 @@ fn parse_points(input: &str) -> Vec<Point> {
         .collect()
 }
 
-fn build_edges(points: &[Point]) -> Vec<Edge> {
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PolygonError {
+    DiagonalEdge { from: Point, to: Point },
+}
+
+impl fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolygonError::DiagonalEdge { from, to } => write!(
+                f,
+                "the edge from {},{} to {},{} is neither horizontal nor vertical",
+                from.x, from.y, to.x, to.y
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PolygonError {}
+
+/// Edges of the polygon through `points` in order, closing back to the
+/// first point. Every edge must be horizontal or vertical.
+fn build_edges(points: &[Point]) -> Result<Vec<Edge>, PolygonError> {
     let n = points.len();
     let mut edges = Vec::with_capacity(n);
     for i in 0..n {
-        edges.push(Edge {
-            p1: points[i],
-            p2: points[(i + 1) % n],
-        });
+        let (p1, p2) = (points[i], points[(i + 1) % n]);
+        if p1.x != p2.x && p1.y != p2.y {
+            return Err(PolygonError::DiagonalEdge { from: p1, to: p2 });
+        }
+        edges.push(Edge { p1, p2 });
     }
-    edges
+    Ok(edges)
 }
 
 fn point_on_segment(p: Point, e: Edge) -> bool {
@@ -46,35 +69,29 @@ fn point_on_segment(p: Point, e: Edge) -> bool {
     }
 }
 
+/// Whether `point` is inside the polygon or on its boundary, using only
+/// integer comparisons on the rectilinear edges from `build_edges`. A ray
+/// cast to the right crosses the vertical edges whose x is beyond the point;
+/// each edge covers its lower end but not its upper one, so a ray through a
+/// vertex or along a horizontal edge is counted once exactly when it passes
+/// from inside to outside.
 fn point_in_poly(point: Point, edges: &[Edge]) -> bool {
-    // ray casting to the right, with boundary inclusion
-    for &e in edges {
-        if point_on_segment(point, e) {
-            return true;
-        }
+    if edges.iter().any(|&e| point_on_segment(point, e)) {
+        return true;
     }
 
-    let mut count = 0;
+    let mut inside = false;
     for &edge in edges {
-        let (x1, y1) = (edge.p1.x, edge.p1.y);
-        let (x2, y2) = (edge.p2.x, edge.p2.y);
-
-        if y1 == y2 {
-            continue; // horizontal edge, skip
+        if edge.p1.y == edge.p2.y {
+            continue;
         }
-
-        // ensure y1 < y2
-        let (x_low, y_low, y_high) = if y1 < y2 { (x1, y1, y2) } else { (x2, y2, y1) };
-
-        if point.y >= y_low && point.y < y_high {
-            let x_int = x_low as f64 + (point.y - y_low) as f64 * (x2 - x1) as f64 / (y2 - y1) as f64;
-            if x_int > point.x as f64 {
-                count += 1;
-            }
+        let (y_low, y_high) = (edge.p1.y.min(edge.p2.y), edge.p1.y.max(edge.p2.y));
+        if point.y >= y_low && point.y < y_high && edge.p1.x > point.x {
+            inside = !inside;
         }
     }
 
-    count % 2 == 1
+    inside
 }
 
 fn solve_part1(input: &str) -> i64 {
@@ -111,12 +128,18 @@ fn main() {
         .expect("failed to read input");
 
     let answer = if part == "part1" {
-        solve_part1(&input)
+        Ok(solve_part1(&input))
     } else {
         solve_part2(&input)
     };
     
-    println!("{answer}");
+    match answer {
+        Ok(answer) => println!("{answer}"),
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    }
 }
 
 fn rect_inside_polygon(a: Point, b: Point, edges: &[Edge]) -> bool {
@@ -138,64 +161,27 @@ fn rect_inside_polygon(a: Point, b: Point, edges: &[Edge]) -> bool {
         }
     }
 
-    // rectangle edges
-    let rect_edges = [
-        Edge { p1: corners[0], p2: corners[1] }, // left
-        Edge { p1: corners[1], p2: corners[3] }, // top
-        Edge { p1: corners[3], p2: corners[2] }, // right
-        Edge { p1: corners[2], p2: corners[0] }, // bottom
-    ];
-
+    // A polygon edge may run along the rectangle's border or end on it, but
+    // an edge that passes through the interior leaves part of it outside.
     for &pe in edges {
-        let p_vertical = pe.p1.x == pe.p2.x;
-        let (px1, py1, px2, py2) = (pe.p1.x, pe.p1.y, pe.p2.x, pe.p2.y);
-        let (py_min, py_max) = (py1.min(py2), py1.max(py2));
-        let (px_min, px_max) = (px1.min(px2), px1.max(px2));
-
-        for &re in &rect_edges {
-            let r_vertical = re.p1.x == re.p2.x;
-            let (rx1, ry1, rx2, ry2) = (re.p1.x, re.p1.y, re.p2.x, re.p2.y);
-            let (ry_min, ry_max) = (ry1.min(ry2), ry1.max(ry2));
-            let (rx_min, rx_max) = (rx1.min(rx2), rx1.max(rx2));
-
-            if p_vertical && r_vertical {
-                if px1 == rx1 && !(py_max < ry_min || py_min > ry_max) {
-                    // overlapping vertical lines on boundary; ok
-                    continue;
-                }
-            } else if !p_vertical && !r_vertical {
-                if py1 == ry1 && !(px_max < rx_min || px_min > rx_max) {
-                    // overlapping horizontal lines; ok
-                    continue;
-                }
-            } else {
-                // one vertical, one horizontal
-                let (vx, vy_min, vy_max) = if p_vertical { (px1, py_min, py_max) } else { (rx1, ry_min, ry_max) };
-                let (hy, hx_min, hx_max) = if p_vertical { (ry1, rx_min, rx_max) } else { (py1, px_min, px_max) };
-                if vx >= hx_min && vx <= hx_max && hy >= vy_min && hy <= vy_max {
-                    // intersection point
-                    let inter = Point { x: vx, y: hy };
-                    // allow if intersection is exactly at a rectangle corner
-                    let is_corner = corners.iter().any(|&c| c.x == inter.x && c.y == inter.y);
-                    if !is_corner {
-                        return false;
-                    }
-                }
-            }
+        let (px_min, px_max) = (pe.p1.x.min(pe.p2.x), pe.p1.x.max(pe.p2.x));
+        let (py_min, py_max) = (pe.p1.y.min(pe.p2.y), pe.p1.y.max(pe.p2.y));
+        if px_max > x1 && px_min < x2 && py_max > y1 && py_min < y2 {
+            return false;
         }
     }
 
     true
 }
 
-fn solve_part2(input: &str) -> i64 {
+fn solve_part2(input: &str) -> Result<i64, PolygonError> {
     let points = parse_points(input);
     let n = points.len();
     if n == 0 {
-        return 0;
+        return Ok(0);
     }
 
-    let edges = build_edges(&points);
+    let edges = build_edges(&points)?;
     let mut max_area = 0;
 
     for i in 0..n {
@@ -211,5 +197,152 @@ fn solve_part2(input: &str) -> i64 {
         }
     }
 
-    max_area
+    Ok(max_area)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_edges, point_in_poly, solve_part1, solve_part2, Point, PolygonError};
+
+    const EXAMPLE: &str = "\
+7,1
+11,1
+11,7
+9,7
+9,5
+2,5
+2,3
+7,3
+";
+
+    fn polygon(vertices: &[(i64, i64)]) -> Vec<super::Edge> {
+        let points: Vec<Point> = vertices.iter().map(|&(x, y)| Point { x, y }).collect();
+        build_edges(&points).unwrap()
+    }
+
+    fn inside(x: i64, y: i64, vertices: &[(i64, i64)]) -> bool {
+        point_in_poly(Point { x, y }, &polygon(vertices))
+    }
+
+    #[test]
+    fn test_example_part1() {
+        assert_eq!(solve_part1(EXAMPLE), 50);
+    }
+
+    #[test]
+    fn test_example_part2() {
+        assert_eq!(solve_part2(EXAMPLE), Ok(24));
+    }
+
+    #[test]
+    fn test_example_corners() {
+        let edges = polygon(&[
+            (7, 1),
+            (11, 1),
+            (11, 7),
+            (9, 7),
+            (9, 5),
+            (2, 5),
+            (2, 3),
+            (7, 3),
+        ]);
+        let inside = |x, y| point_in_poly(Point { x, y }, &edges);
+        // The corners of the largest rectangle made of red and green tiles.
+        assert!(inside(9, 5) && inside(2, 3) && inside(9, 3) && inside(2, 5));
+        assert!(inside(8, 2) && inside(10, 6));
+        assert!(!inside(2, 1) && !inside(8, 6) && !inside(12, 4));
+    }
+
+    #[test]
+    fn test_interior_and_exterior() {
+        let square = [(0, 0), (10, 0), (10, 10), (0, 10)];
+        assert!(inside(5, 5, &square));
+        assert!(!inside(11, 5, &square));
+        assert!(!inside(-1, 5, &square));
+        assert!(!inside(5, 11, &square));
+        assert!(!inside(5, -1, &square));
+    }
+
+    #[test]
+    fn test_boundary_and_vertices() {
+        let square = [(0, 0), (10, 0), (10, 10), (0, 10)];
+        for (x, y) in [(0, 0), (10, 0), (10, 10), (0, 10), (5, 0), (10, 5), (0, 7)] {
+            assert!(inside(x, y, &square), "({x}, {y})");
+        }
+        // On the line through an edge, but past its end.
+        assert!(!inside(11, 0, &square));
+        assert!(!inside(0, -3, &square));
+    }
+
+    #[test]
+    fn test_ray_through_vertices_and_collinear_edges() {
+        // A U shape: the ray from (1, 5) runs along the bottom of the notch
+        // and through two vertices, from (3, 5) to (7, 5).
+        let u = [
+            (0, 0),
+            (10, 0),
+            (10, 10),
+            (7, 10),
+            (7, 5),
+            (3, 5),
+            (3, 10),
+            (0, 10),
+        ];
+        assert!(inside(1, 5, &u));
+        assert!(inside(5, 5, &u));
+        assert!(!inside(5, 6, &u));
+        assert!(inside(8, 10, &u));
+        assert!(!inside(-1, 5, &u));
+        assert!(!inside(-1, 10, &u));
+        assert!(inside(1, 4, &u));
+        // A step: the ray from (-1, 5) passes the vertex (4, 5) where the
+        // boundary turns, and crosses the polygon once there.
+        let step = [(0, 0), (4, 0), (4, 5), (8, 5), (8, 9), (0, 9)];
+        assert!(!inside(-1, 5, &step));
+        assert!(inside(2, 5, &step));
+        assert!(!inside(9, 5, &step));
+        assert!(!inside(6, 4, &step));
+    }
+
+    #[test]
+    fn test_large_coordinates() {
+        // Beyond 2^53 neighbouring integers are no longer distinct as f64.
+        let big = 1i64 << 60;
+        let square = [
+            (big, big),
+            (big + 2, big),
+            (big + 2, big + 2),
+            (big, big + 2),
+        ];
+        assert!(inside(big + 1, big + 1, &square));
+        assert!(inside(big + 2, big + 1, &square));
+        assert!(!inside(big + 3, big + 1, &square));
+        assert!(!inside(big - 1, big + 1, &square));
+        let extremes = [
+            (i64::MIN, i64::MIN),
+            (i64::MAX, i64::MIN),
+            (i64::MAX, i64::MAX),
+            (i64::MIN, i64::MAX),
+        ];
+        assert!(inside(0, 0, &extremes));
+        assert!(inside(i64::MAX, 0, &extremes));
+        assert!(inside(i64::MAX - 1, i64::MAX - 1, &extremes));
+    }
+
+    #[test]
+    fn test_diagonal_edges_are_rejected() {
+        let points = [
+            Point { x: 0, y: 0 },
+            Point { x: 4, y: 0 },
+            Point { x: 0, y: 3 },
+        ];
+        assert_eq!(
+            build_edges(&points).err(),
+            Some(PolygonError::DiagonalEdge {
+                from: Point { x: 4, y: 0 },
+                to: Point { x: 0, y: 3 }
+            })
+        );
+        assert!(solve_part2("0,0\n4,0\n0,3\n").is_err());
+    }
 }